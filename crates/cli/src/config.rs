@@ -1,5 +1,14 @@
+use crate::constants::RATE_LIMIT_FILE_NAME;
+use crate::format::InputFormat;
+use crate::paths;
+use crate::probe::ProbeCommand;
+use crate::profile::{ConfigFile, Profile};
+use crate::template::{self, Template};
 use clap::{Arg, ArgAction, ArgMatches, Command};
-use freemobile_api::{Credentials, FreeMobileError};
+use freemobile_api::{
+    ApiKey, ChunkOptions, ControlChars, Credentials, FreeMobileError, Overflow, SanitizeOptions,
+    SanitizePolicy, SplitStrategy,
+};
 use is_terminal::IsTerminal;
 use std::env;
 use std::path::PathBuf;
@@ -8,10 +17,34 @@ type Validator = fn(&str) -> Result<(), FreeMobileError>;
 
 #[derive(Debug, Clone)]
 pub struct Config {
-    pub credentials: Credentials,
+    /// FreeMobile credentials, not required in dry-run mode
+    pub credentials: Option<Credentials>,
     pub message: Option<String>,
     pub file_path: Option<PathBuf>,
+    /// Input markup, from `--format` or the extension of `--file` or `--template`
+    pub input_format: InputFormat,
+    /// Message template, from `--template` and `--template-dir`
+    pub template_path: Option<PathBuf>,
+    /// Template variables from `--var`, overriding all other sources
+    pub template_vars: Vec<(String, String)>,
+    /// Value of the `{exit_code}` template variable
+    pub exit_code: Option<i32>,
+    pub resume_path: Option<PathBuf>,
+    /// API endpoint overriding `API_URL`, e.g. a local mock server
+    pub api_url: Option<String>,
+    /// Chunking settings, from `--max-parts`, `--overflow` and `--split`
+    pub chunk_options: ChunkOptions,
+    /// Sanitizer settings, from `--emoji-policy`, `--emoji-placeholder`, `--control-chars` and `--gsm7`
+    pub sanitize_options: SanitizeOptions,
+    /// TOML file extending the built-in emoji table
+    pub emoji_table_path: Option<PathBuf>,
+    /// File of the rate limit shared between runs, `None` to keep it in memory
+    pub rate_limit_file: Option<PathBuf>,
+    /// `probe-emojis` step to run instead of sending a message
+    pub probe: Option<ProbeCommand>,
     pub verbose: bool,
+    pub dry_run: bool,
+    pub json: bool,
 }
 
 impl Config {
@@ -23,27 +56,206 @@ impl Config {
     }
 
     pub fn from_matches(matches: &ArgMatches) -> Result<Self, FreeMobileError> {
-        let user = Self::get_user_id(matches)?;
-        let pass = Self::get_api_key(matches)?;
-        let credentials = Credentials::new(user, pass);
+        let dry_run = matches.get_flag("dry-run");
+        let probe = match matches.subcommand() {
+            Some(("probe-emojis", matches)) => Some(ProbeCommand::from_matches(matches)?),
+            _ => None,
+        };
+        if probe.is_some()
+            && let Some(arg) = ["message", "file", "resume", "template"]
+                .into_iter()
+                .find(|&arg| matches.get_one::<String>(arg).is_some())
+        {
+            return Err(FreeMobileError::ConfigError(format!(
+                "--{} cannot be used with probe-emojis",
+                arg
+            )));
+        }
+
+        let profile = Self::load_profile(matches)?;
+
+        // A dry run or a probe report never reaches the API, so it does not need credentials
+        let offline = dry_run || matches!(probe, Some(ProbeCommand::Report { .. }));
+        let credentials = if offline {
+            None
+        } else {
+            let user = Self::get_user_id(matches, &profile)?;
+            let pass = Self::get_api_key(matches, &profile)?;
+            Some(Credentials::new(user, pass))
+        };
+
+        let max_chunks = matches
+            .get_one::<u64>("max-parts")
+            .copied()
+            .or(profile.max_parts)
+            .map(|max| max as usize);
+        let overflow = Self::get_option(matches, "overflow", &profile.overflow);
+        // The maximum may come from the profile, so it is only checked once merged
+        if overflow.is_some() && max_chunks.is_none() {
+            return Err(FreeMobileError::ConfigError(
+                "--overflow requires --max-parts".to_string(),
+            ));
+        }
+        let chunk_options = ChunkOptions {
+            max_chunks,
+            overflow: overflow
+                .map(|name| name.parse())
+                .transpose()?
+                .unwrap_or_default(),
+            split: Self::get_option(matches, "split", &profile.split)
+                .map(|name| name.parse())
+                .transpose()?
+                .unwrap_or_default(),
+            ..ChunkOptions::default()
+        };
+
+        let sanitize_options = SanitizeOptions {
+            policy: Self::get_sanitize_policy(matches, &profile)?,
+            controls: Self::get_option(matches, "control-chars", &profile.control_chars)
+                .map(|name| name.parse())
+                .transpose()?
+                .unwrap_or_default(),
+            transliterate: matches.get_flag("gsm7") || profile.gsm7.unwrap_or(false),
+            ..SanitizeOptions::default()
+        };
+
+        let file_path = matches.get_one::<String>("file").map(PathBuf::from);
+        let template_path = matches
+            .get_one::<String>("template")
+            .map(|name| {
+                let dir = matches
+                    .get_one::<String>("template-dir")
+                    .map(PathBuf::from)
+                    .or_else(|| profile.template_dir.clone());
+                Template::find(name, dir.as_deref())
+            })
+            .transpose()?;
+        let template_vars = matches
+            .get_many::<String>("var")
+            .unwrap_or_default()
+            .map(|assignment| template::parse_assignment(assignment))
+            .collect::<Result<Vec<_>, _>>()?;
+        let input_format = match matches.get_one::<String>("format") {
+            Some(name) => name.parse()?,
+            None => file_path
+                .as_deref()
+                .or(template_path.as_deref())
+                .map(InputFormat::from_path)
+                .unwrap_or_default(),
+        };
 
         let config = Config {
             credentials,
             message: matches.get_one::<String>("message").cloned(),
-            file_path: matches.get_one::<String>("file").map(PathBuf::from),
+            file_path,
+            input_format,
+            template_path,
+            template_vars,
+            exit_code: matches.get_one::<i32>("exit-code").copied(),
+            resume_path: matches.get_one::<String>("resume").map(PathBuf::from),
+            api_url: Self::get_option(matches, "api-url", &profile.api_url).cloned(),
+            chunk_options,
+            sanitize_options,
+            emoji_table_path: matches
+                .get_one::<String>("emoji-table")
+                .map(PathBuf::from)
+                .or_else(|| profile.emoji_table.clone()),
+            rate_limit_file: Self::get_rate_limit_file(matches, &profile),
+            probe,
             verbose: matches.get_flag("verbose"),
+            dry_run,
+            json: matches.get_flag("json"),
         };
 
         Ok(config)
     }
 
+    /// Profile of the config file given with `--config`, or found in the config directory
+    ///
+    /// Without a config file, an empty profile is returned, unless `--profile` was given.
+    fn load_profile(matches: &ArgMatches) -> Result<Profile, FreeMobileError> {
+        let name = matches.get_one::<String>("profile").map(String::as_str);
+        let path = match matches.get_one::<String>("config") {
+            Some(path) => PathBuf::from(path),
+            None => match ConfigFile::default_path() {
+                Some(path) if path.is_file() => path,
+                default_path => {
+                    return match name {
+                        Some(name) => Err(FreeMobileError::ConfigError(format!(
+                            "Profile '{}' requested but no config file found{}, use --config",
+                            name,
+                            default_path
+                                .map(|path| format!(" at {}", path.display()))
+                                .unwrap_or_default()
+                        ))),
+                        None => Ok(Profile::default()),
+                    };
+                }
+            },
+        };
+
+        let file = ConfigFile::load(path)?;
+        Ok(file.profile(name)?.cloned().unwrap_or_default())
+    }
+
+    /// Option from the command line or the environment, else from the profile
+    fn get_option<'a>(
+        matches: &'a ArgMatches,
+        name: &str,
+        profile_value: &'a Option<String>,
+    ) -> Option<&'a String> {
+        matches.get_one::<String>(name).or(profile_value.as_ref())
+    }
+
+    /// Shared rate limit file, from the command line, the environment, the profile or the state directory
+    fn get_rate_limit_file(matches: &ArgMatches, profile: &Profile) -> Option<PathBuf> {
+        if matches.get_flag("no-shared-rate-limit") {
+            return None;
+        }
+        match matches.get_one::<String>("rate-limit-file") {
+            Some(path) => Some(PathBuf::from(path)),
+            None if profile.shared_rate_limit == Some(false) => None,
+            None => profile
+                .rate_limit_file
+                .clone()
+                .or_else(|| paths::state_dir().map(|dir| dir.join(RATE_LIMIT_FILE_NAME))),
+        }
+    }
+
+    /// Emoji policy, a custom placeholder only being allowed with the placeholder policy
+    fn get_sanitize_policy(
+        matches: &ArgMatches,
+        profile: &Profile,
+    ) -> Result<SanitizePolicy, FreeMobileError> {
+        let policy = Self::get_option(matches, "emoji-policy", &profile.emoji_policy)
+            .map(|name| name.parse())
+            .transpose()?
+            .unwrap_or_default();
+
+        match (
+            policy,
+            Self::get_option(matches, "emoji-placeholder", &profile.emoji_placeholder),
+        ) {
+            (SanitizePolicy::Placeholder(_), Some(placeholder)) => {
+                Ok(SanitizePolicy::Placeholder(placeholder.clone()))
+            }
+            (policy, Some(_)) => Err(FreeMobileError::ConfigError(format!(
+                "--emoji-placeholder cannot be used with the '{}' emoji policy",
+                policy
+            ))),
+            (policy, None) => Ok(policy),
+        }
+    }
+
     fn build_cli() -> Command {
         Command::new("send-sms")
             .version(env!("CARGO_PKG_VERSION"))
             .author("davlgd")
             .about("Send SMS messages via FreeMobile API")
+            .subcommand(ProbeCommand::command())
             .arg(
                 Arg::new("user")
+                    .global(true)
                     .short('u')
                     .long("user")
                     .env("FREEMOBILE_USER")
@@ -53,6 +265,7 @@ impl Config {
             )
             .arg(
                 Arg::new("pass")
+                    .global(true)
                     .short('p')
                     .long("pass")
                     .env("FREEMOBILE_PASS")
@@ -60,6 +273,22 @@ impl Config {
                     .help("FreeMobile API key")
                     .required(false),
             )
+            .arg(
+                Arg::new("config")
+                    .global(true)
+                    .long("config")
+                    .env("FREEMOBILE_CONFIG")
+                    .value_name("PATH")
+                    .help("TOML config file of profiles [default: <config dir>/send-sms/config.toml]"),
+            )
+            .arg(
+                Arg::new("profile")
+                    .global(true)
+                    .long("profile")
+                    .env("FREEMOBILE_PROFILE")
+                    .value_name("NAME")
+                    .help("Config file profile providing credentials and default options"),
+            )
             .arg(
                 Arg::new("message")
                     .short('m')
@@ -76,8 +305,148 @@ impl Config {
                     .help("Read message from file")
                     .conflicts_with("message"),
             )
+            .arg(
+                Arg::new("format")
+                    .long("format")
+                    .value_name("FORMAT")
+                    .help("Markup of the message, rendered to plain text [default: from the file extension, else text]")
+                    .value_parser(InputFormat::NAMES),
+            )
+            .arg(
+                Arg::new("template")
+                    .short('t')
+                    .long("template")
+                    .value_name("NAME|PATH")
+                    .help("Build the message from a template file, or a template of the template directory")
+                    .conflicts_with_all(["message", "file"]),
+            )
+            .arg(
+                Arg::new("template-dir")
+                    .long("template-dir")
+                    .env("FREEMOBILE_TEMPLATE_DIR")
+                    .value_name("DIR")
+                    .help("Directory of named templates [default: <config dir>/send-sms/templates]"),
+            )
+            .arg(
+                Arg::new("var")
+                    .long("var")
+                    .value_name("KEY=VALUE")
+                    .help("Template variable, overriding JSON from stdin, built-ins and environment (repeatable)")
+                    .action(ArgAction::Append)
+                    .requires("template"),
+            )
+            .arg(
+                Arg::new("exit-code")
+                    .long("exit-code")
+                    .value_name("CODE")
+                    .help("Value of the {exit_code} template variable, e.g. $?")
+                    .value_parser(clap::value_parser!(i32))
+                    .allow_negative_numbers(true)
+                    .requires("template"),
+            )
+            .arg(
+                Arg::new("resume")
+                    .long("resume")
+                    .value_name("STATE_FILE")
+                    .help("Send the remaining parts of a partially sent message")
+                    .conflicts_with_all(["message", "file", "template", "dry-run"]),
+            )
+            .arg(
+                Arg::new("max-parts")
+                    .long("max-parts")
+                    .value_name("N")
+                    .help("Maximum number of SMS a message can be split into")
+                    .value_parser(clap::value_parser!(u64).range(1..)),
+            )
+            .arg(
+                Arg::new("overflow")
+                    .long("overflow")
+                    .value_name("STRATEGY")
+                    .help("What to do with messages needing more parts [default: refuse]")
+                    .value_parser(Overflow::NAMES),
+            )
+            .arg(
+                Arg::new("split")
+                    .long("split")
+                    .value_name("STRATEGY")
+                    .help("Where long messages are preferably split [default: whitespace]")
+                    .value_parser(SplitStrategy::NAMES),
+            )
+            .arg(
+                Arg::new("emoji-policy")
+                    .long("emoji-policy")
+                    .value_name("POLICY")
+                    .help("What unsupported emojis are replaced with [default: placeholder]")
+                    .value_parser(SanitizePolicy::NAMES),
+            )
+            .arg(
+                Arg::new("emoji-placeholder")
+                    .long("emoji-placeholder")
+                    .value_name("TEXT")
+                    .help("Text replacing unsupported emojis with the placeholder policy [default: []]"),
+            )
+            .arg(
+                Arg::new("emoji-table")
+                    .long("emoji-table")
+                    .env("FREEMOBILE_EMOJI_TABLE")
+                    .value_name("PATH")
+                    .help("TOML file of additional supported emojis and substitutions"),
+            )
+            .arg(
+                Arg::new("control-chars")
+                    .long("control-chars")
+                    .value_name("HANDLING")
+                    .help("What control characters other than newlines and tabs become [default: remove]")
+                    .value_parser(ControlChars::NAMES),
+            )
+            .arg(
+                Arg::new("gsm7")
+                    .long("gsm7")
+                    .visible_alias("ascii-safe")
+                    .help("Replace typographic characters and diacritics missing from GSM-7 to avoid UCS-2")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("api-url")
+                    .global(true)
+                    .long("api-url")
+                    .env("FREEMOBILE_API_URL")
+                    .value_name("URL")
+                    .help("FreeMobile API endpoint, e.g. a local mock server"),
+            )
+            .arg(
+                Arg::new("rate-limit-file")
+                    .global(true)
+                    .long("rate-limit-file")
+                    .env("FREEMOBILE_RATE_LIMIT_FILE")
+                    .value_name("PATH")
+                    .help("File of the rate limit shared by all runs [default: <state dir>/send-sms/send-sms.ratelimit]"),
+            )
+            .arg(
+                Arg::new("no-shared-rate-limit")
+                    .global(true)
+                    .long("no-shared-rate-limit")
+                    .help("Keep the rate limit of this run in memory, without sharing it")
+                    .action(ArgAction::SetTrue)
+                    .conflicts_with("rate-limit-file"),
+            )
+            .arg(
+                Arg::new("dry-run")
+                    .global(true)
+                    .long("dry-run")
+                    .help("Show the parts that would be sent, without sending anything")
+                    .action(ArgAction::SetTrue),
+            )
+            .arg(
+                Arg::new("json")
+                    .long("json")
+                    .help("Print the dry run as JSON")
+                    .action(ArgAction::SetTrue)
+                    .requires("dry-run"),
+            )
             .arg(
                 Arg::new("verbose")
+                    .global(true)
                     .short('v')
                     .long("verbose")
                     .help("Verbose output")
@@ -104,19 +473,30 @@ impl Config {
                     FreeMobileError::ConfigError(error_message.to_string())
                 }
             })
-            .and_then(|value| {
-                if value.trim().is_empty() {
-                    Err(FreeMobileError::ConfigError(format!(
-                        "{} cannot be empty",
-                        field_name
-                    )))
-                } else {
-                    if let Some(validate) = validator {
-                        validate(&value)?;
-                    }
-                    Ok(value)
-                }
-            })
+            .and_then(|value| Self::check_value(value, field_name, validator))
+    }
+
+    fn check_value(
+        value: String,
+        field_name: &str,
+        validator: Option<Validator>,
+    ) -> Result<String, FreeMobileError> {
+        if value.trim().is_empty() {
+            Err(FreeMobileError::ConfigError(format!(
+                "{} cannot be empty",
+                field_name
+            )))
+        } else {
+            if let Some(validate) = validator {
+                validate(&value)?;
+            }
+            Ok(value)
+        }
+    }
+
+    /// Whether a value was given on the command line or in the environment
+    fn is_set(matches: &ArgMatches, cli_arg: &str, env_var: &str) -> bool {
+        matches.get_one::<String>(cli_arg).is_some() || env::var(env_var).is_ok()
     }
 
     fn validate_user_id(user_id: &str) -> Result<(), FreeMobileError> {
@@ -129,13 +509,19 @@ impl Config {
         }
     }
 
-    fn get_user_id(matches: &ArgMatches) -> Result<String, FreeMobileError> {
-        // Try CLI args and env vars first
+    fn get_user_id(matches: &ArgMatches, profile: &Profile) -> Result<String, FreeMobileError> {
+        // The profile only applies when neither CLI args nor env vars are set
+        if !Self::is_set(matches, "user", "FREEMOBILE_USER")
+            && let Some(ref user_id) = profile.user
+        {
+            return Self::check_value(user_id.clone(), "User ID", Some(Self::validate_user_id));
+        }
+
         let result = Self::get_config_value(
             matches,
             "user",
             "FREEMOBILE_USER",
-            "FreeMobile user ID not found. Set FREEMOBILE_USER environment variable or use -u option",
+            "FreeMobile user ID not found. Set FREEMOBILE_USER environment variable, use -u option or a config file profile",
             "User ID",
             Some(Self::validate_user_id),
         );
@@ -153,19 +539,30 @@ impl Config {
         }
     }
 
-    fn get_api_key(matches: &ArgMatches) -> Result<String, FreeMobileError> {
-        // Try CLI args and env vars first
+    fn get_api_key(matches: &ArgMatches, profile: &Profile) -> Result<ApiKey, FreeMobileError> {
+        // The profile only applies when neither CLI args nor env vars are set
+        if !Self::is_set(matches, "pass", "FREEMOBILE_PASS")
+            && let Some(api_key) = profile.api_key()?
+        {
+            if api_key.expose().trim().is_empty() {
+                return Err(FreeMobileError::ConfigError(
+                    "API key cannot be empty".to_string(),
+                ));
+            }
+            return Ok(api_key);
+        }
+
         let result = Self::get_config_value(
             matches,
             "pass",
             "FREEMOBILE_PASS",
-            "FreeMobile API key not found. Set FREEMOBILE_PASS environment variable or use -p option",
+            "FreeMobile API key not found. Set FREEMOBILE_PASS environment variable, use -p option or a config file profile",
             "API key",
             None,
         );
 
         match result {
-            Ok(api_key) => Ok(api_key),
+            Ok(api_key) => Ok(ApiKey::new(api_key)),
             Err(err) => {
                 // Don't prompt during tests (when running in CI or non-TTY environment)
                 if cfg!(test) || !std::io::stdin().is_terminal() {
@@ -202,7 +599,7 @@ impl Config {
         Ok(user_id)
     }
 
-    fn prompt_for_api_key() -> Result<String, FreeMobileError> {
+    fn prompt_for_api_key() -> Result<ApiKey, FreeMobileError> {
         use inquire::Password;
 
         let api_key = Password::new("FreeMobile API Key:")
@@ -217,9 +614,10 @@ impl Config {
                 } else {
                     FreeMobileError::ConfigError(format!("Failed to read API key: {}", e))
                 }
-            })?;
+            })
+            .map(ApiKey::new)?;
 
-        if api_key.trim().is_empty() {
+        if api_key.expose().trim().is_empty() {
             return Err(FreeMobileError::ConfigError(
                 "API key cannot be empty".to_string(),
             ));
@@ -233,6 +631,79 @@ impl Config {
 mod tests {
     use super::*;
     use clap::ArgMatches;
+    use std::sync::{Mutex, MutexGuard, PoisonError};
+
+    /// Environment variables read by the CLI
+    const CLI_ENV_VARS: [&str; 8] = [
+        "FREEMOBILE_USER",
+        "FREEMOBILE_PASS",
+        "FREEMOBILE_CONFIG",
+        "FREEMOBILE_PROFILE",
+        "FREEMOBILE_TEMPLATE_DIR",
+        "FREEMOBILE_EMOJI_TABLE",
+        "FREEMOBILE_API_URL",
+        "FREEMOBILE_RATE_LIMIT_FILE",
+    ];
+
+    /// Serializes the tests reading the environment
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Environment of a test, restored when dropped
+    ///
+    /// The CLI env vars of the user are cleared, and `FREEMOBILE_CONFIG` points
+    /// to an empty config file instead of the user's one.
+    struct EnvGuard {
+        saved: Vec<(&'static str, Option<String>)>,
+        _config: tempfile::NamedTempFile,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl EnvGuard {
+        fn set(vars: &[(&'static str, &str)]) -> Self {
+            let lock = ENV_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+            let config = tempfile::NamedTempFile::new().unwrap();
+            let saved = CLI_ENV_VARS
+                .iter()
+                .map(|&name| (name, env::var(name).ok()))
+                .collect();
+
+            unsafe {
+                for name in CLI_ENV_VARS {
+                    env::remove_var(name);
+                }
+                env::set_var("FREEMOBILE_CONFIG", config.path());
+                for (name, value) in vars {
+                    env::set_var(name, value);
+                }
+            }
+            Self {
+                saved,
+                _config: config,
+                _lock: lock,
+            }
+        }
+
+        fn clean() -> Self {
+            Self::set(&[])
+        }
+
+        fn credentials(user: &str, pass: &str) -> Self {
+            Self::set(&[("FREEMOBILE_USER", user), ("FREEMOBILE_PASS", pass)])
+        }
+    }
+
+    impl Drop for EnvGuard {
+        fn drop(&mut self) {
+            for (name, value) in self.saved.drain(..) {
+                unsafe {
+                    match value {
+                        Some(value) => env::set_var(name, value),
+                        None => env::remove_var(name),
+                    }
+                }
+            }
+        }
+    }
 
     fn create_test_matches(args: &[&str]) -> ArgMatches {
         Config::build_cli().try_get_matches_from(args).unwrap()
@@ -240,22 +711,21 @@ mod tests {
 
     #[test]
     fn test_config_with_message() {
-        unsafe {
-            env::set_var("FREEMOBILE_USER", "12345678");
-            env::set_var("FREEMOBILE_PASS", "testkey");
-        }
+        let _env = EnvGuard::credentials("12345678", "testkey");
 
         let matches = create_test_matches(&["send-sms", "-m", "Hello world"]);
         let config = Config::from_matches(&matches).unwrap();
 
-        assert_eq!(config.credentials.user, "12345678");
-        assert_eq!(config.credentials.pass, "testkey");
+        let credentials = config.credentials.unwrap();
+        assert_eq!(credentials.user, "12345678");
+        assert_eq!(credentials.pass.expose(), "testkey");
         assert_eq!(config.message.unwrap(), "Hello world");
         assert!(!config.verbose);
     }
 
     #[test]
     fn test_invalid_user_id() {
+        let _env = EnvGuard::clean();
         let matches =
             create_test_matches(&["send-sms", "-u", "invalid", "-p", "key", "-m", "test"]);
         let result = Config::from_matches(&matches);
@@ -269,10 +739,7 @@ mod tests {
 
     #[test]
     fn test_no_message_source_is_valid() {
-        unsafe {
-            env::set_var("FREEMOBILE_USER", "12345678");
-            env::set_var("FREEMOBILE_PASS", "testkey");
-        }
+        let _env = EnvGuard::credentials("12345678", "testkey");
 
         let matches = create_test_matches(&["send-sms"]);
         let result = Config::from_matches(&matches);
@@ -282,14 +749,469 @@ mod tests {
         let config = result.unwrap();
         assert!(config.message.is_none());
         assert!(config.file_path.is_none());
+        assert!(config.resume_path.is_none());
         assert!(!config.verbose);
     }
 
+    #[test]
+    fn test_resume_conflicts_with_message() {
+        let _env = EnvGuard::clean();
+        let result = Config::build_cli().try_get_matches_from([
+            "send-sms",
+            "--resume",
+            "state.json",
+            "-m",
+            "test",
+        ]);
+        assert!(result.is_err());
+
+        let matches = create_test_matches(&[
+            "send-sms",
+            "-u",
+            "12345678",
+            "-p",
+            "key",
+            "--resume",
+            "state.json",
+        ]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.resume_path, Some(PathBuf::from("state.json")));
+    }
+
+    #[test]
+    fn test_dry_run_without_credentials() {
+        let _env = EnvGuard::clean();
+        let matches = create_test_matches(&[
+            "send-sms",
+            "-u",
+            "invalid",
+            "--dry-run",
+            "--json",
+            "-m",
+            "hi",
+        ]);
+        let config = Config::from_matches(&matches).unwrap();
+
+        assert!(config.dry_run);
+        assert!(config.json);
+        assert!(config.credentials.is_none());
+
+        let result = Config::build_cli().try_get_matches_from(["send-sms", "--json", "-m", "hi"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_max_parts() {
+        let _env = EnvGuard::clean();
+        let matches = create_test_matches(&[
+            "send-sms",
+            "--dry-run",
+            "--max-parts",
+            "3",
+            "--overflow",
+            "head-tail",
+        ]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.chunk_options.max_chunks, Some(3));
+        assert_eq!(config.chunk_options.overflow, Overflow::HeadTail);
+
+        let matches = create_test_matches(&["send-sms", "--dry-run", "--max-parts", "2"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.chunk_options.overflow, Overflow::Refuse);
+
+        let result = Config::build_cli().try_get_matches_from(["send-sms", "--max-parts", "0"]);
+        assert!(result.is_err());
+
+        let matches = create_test_matches(&["send-sms", "--dry-run", "--overflow", "truncate"]);
+        let error = Config::from_matches(&matches).unwrap_err().to_string();
+        assert!(error.contains("--overflow requires --max-parts"));
+    }
+
+    #[test]
+    fn test_split() {
+        let _env = EnvGuard::clean();
+        let matches = create_test_matches(&["send-sms", "--dry-run", "--split", "paragraph"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.chunk_options.split, SplitStrategy::Paragraph);
+
+        let matches = create_test_matches(&["send-sms", "--dry-run"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.chunk_options.split, SplitStrategy::Whitespace);
+
+        let result = Config::build_cli().try_get_matches_from(["send-sms", "--split", "word"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_emoji_policy() {
+        let _env = EnvGuard::clean();
+        let matches = create_test_matches(&["send-sms", "--dry-run", "--emoji-policy", "closest"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.sanitize_options.policy, SanitizePolicy::Closest);
+
+        let matches = create_test_matches(&["send-sms", "--dry-run"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.sanitize_options.policy, SanitizePolicy::default());
+
+        let matches = create_test_matches(&["send-sms", "--dry-run", "--emoji-placeholder", "(?)"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(
+            config.sanitize_options.policy,
+            SanitizePolicy::Placeholder("(?)".to_string())
+        );
+
+        let matches = create_test_matches(&[
+            "send-sms",
+            "--dry-run",
+            "--emoji-policy",
+            "drop",
+            "--emoji-placeholder",
+            "(?)",
+        ]);
+        assert!(Config::from_matches(&matches).is_err());
+
+        let result =
+            Config::build_cli().try_get_matches_from(["send-sms", "--emoji-policy", "emoji"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_control_chars() {
+        let _env = EnvGuard::clean();
+        let matches = create_test_matches(&["send-sms", "--dry-run", "--control-chars", "escape"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.sanitize_options.controls, ControlChars::Escape);
+
+        let matches = create_test_matches(&["send-sms", "--dry-run"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.sanitize_options, SanitizeOptions::default());
+
+        let result =
+            Config::build_cli().try_get_matches_from(["send-sms", "--control-chars", "strip"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_gsm7() {
+        let _env = EnvGuard::clean();
+        for flag in ["--gsm7", "--ascii-safe"] {
+            let matches = create_test_matches(&["send-sms", "--dry-run", flag]);
+            let config = Config::from_matches(&matches).unwrap();
+            assert!(config.sanitize_options.transliterate);
+        }
+
+        let matches = create_test_matches(&["send-sms", "--dry-run"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert!(!config.sanitize_options.transliterate);
+    }
+
+    #[test]
+    fn test_input_format() {
+        let _env = EnvGuard::clean();
+        let matches = create_test_matches(&["send-sms", "--dry-run", "-f", "NOTES.md"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.input_format, InputFormat::Markdown);
+
+        let matches = create_test_matches(&[
+            "send-sms",
+            "--dry-run",
+            "-f",
+            "NOTES.md",
+            "--format",
+            "text",
+        ]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.input_format, InputFormat::Text);
+
+        let matches = create_test_matches(&["send-sms", "--dry-run", "--format", "html"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.input_format, InputFormat::Html);
+
+        let matches = create_test_matches(&["send-sms", "--dry-run", "-m", "**hi**"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.input_format, InputFormat::Text);
+
+        let result = Config::build_cli().try_get_matches_from(["send-sms", "--format", "rst"]);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_template() {
+        let _env = EnvGuard::clean();
+        let tmp = tempfile::TempDir::new().unwrap();
+        std::fs::write(tmp.path().join("alert.md"), "**{hostname}**: {status}").unwrap();
+        let dir = tmp.path().to_str().unwrap();
+
+        let matches = create_test_matches(&[
+            "send-sms",
+            "--dry-run",
+            "--template",
+            "alert",
+            "--template-dir",
+            dir,
+            "--var",
+            "status=down",
+            "--var",
+            "check=disk=full",
+            "--exit-code",
+            "-1",
+        ]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.template_path, Some(tmp.path().join("alert.md")));
+        assert_eq!(config.input_format, InputFormat::Markdown);
+        assert_eq!(
+            config.template_vars,
+            vec![
+                ("status".to_string(), "down".to_string()),
+                ("check".to_string(), "disk=full".to_string()),
+            ]
+        );
+        assert_eq!(config.exit_code, Some(-1));
+
+        let matches = create_test_matches(&[
+            "send-sms",
+            "--dry-run",
+            "-t",
+            "unknown",
+            "--template-dir",
+            dir,
+        ]);
+        assert!(Config::from_matches(&matches).is_err());
+
+        let matches = create_test_matches(&[
+            "send-sms",
+            "--dry-run",
+            "-t",
+            "alert",
+            "--template-dir",
+            dir,
+            "--var",
+            "status",
+        ]);
+        assert!(Config::from_matches(&matches).is_err());
+
+        for args in [
+            &["send-sms", "--var", "status=down"][..],
+            &["send-sms", "--exit-code", "1"],
+            &["send-sms", "-t", "alert", "-m", "Hello"],
+            &["send-sms", "-t", "alert", "-f", "message.txt"],
+        ] {
+            assert!(Config::build_cli().try_get_matches_from(args).is_err());
+        }
+    }
+
+    #[test]
+    fn test_profile_precedence() {
+        let env = EnvGuard::clean();
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("config.toml");
+        std::fs::write(
+            &path,
+            r#"
+[profiles.ops]
+user = "87654321"
+pass = "ops-key"
+max_parts = 2
+emoji_policy = "closest"
+"#,
+        )
+        .unwrap();
+        let config_path = path.to_str().unwrap();
+
+        // CLI args win over the profile
+        let matches = create_test_matches(&[
+            "send-sms",
+            "--config",
+            config_path,
+            "--profile",
+            "ops",
+            "-u",
+            "11111111",
+            "-p",
+            "cli-key",
+            "--max-parts",
+            "5",
+            "-m",
+            "test",
+        ]);
+        let config = Config::from_matches(&matches).unwrap();
+        let credentials = config.credentials.unwrap();
+        assert_eq!(credentials.user, "11111111");
+        assert_eq!(credentials.pass.expose(), "cli-key");
+        assert_eq!(config.chunk_options.max_chunks, Some(5));
+        assert_eq!(config.sanitize_options.policy, SanitizePolicy::Closest);
+
+        // Env vars win over the profile
+        drop(env);
+        let _env = EnvGuard::set(&[("FREEMOBILE_USER", "12345678")]);
+        let matches = create_test_matches(&[
+            "send-sms",
+            "--config",
+            config_path,
+            "--profile",
+            "ops",
+            "-p",
+            "cli-key",
+        ]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.credentials.unwrap().user, "12345678");
+        assert_eq!(config.chunk_options.max_chunks, Some(2));
+
+        // The maximum of the profile satisfies --overflow
+        let matches = create_test_matches(&[
+            "send-sms",
+            "--dry-run",
+            "--config",
+            config_path,
+            "--profile",
+            "ops",
+            "--overflow",
+            "truncate",
+        ]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.chunk_options.max_chunks, Some(2));
+        assert_eq!(config.chunk_options.overflow, Overflow::Truncate);
+
+        let matches = create_test_matches(&[
+            "send-sms",
+            "--dry-run",
+            "--config",
+            config_path,
+            "--profile",
+            "home",
+        ]);
+        let error = Config::from_matches(&matches).unwrap_err().to_string();
+        assert!(error.contains("Unknown profile 'home', expected one of: ops"));
+
+        let matches = create_test_matches(&[
+            "send-sms",
+            "--dry-run",
+            "--config",
+            tmp.path().join("missing.toml").to_str().unwrap(),
+        ]);
+        assert!(Config::from_matches(&matches).is_err());
+    }
+
+    #[test]
+    fn test_profile_credentials() {
+        let _env = EnvGuard::clean();
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("config.toml");
+        std::fs::write(
+            &path,
+            "[profiles.default]\nuser = \"87654321\"\npass_command = \"echo profile-key\"\n",
+        )
+        .unwrap();
+
+        let matches =
+            create_test_matches(&["send-sms", "--config", path.to_str().unwrap(), "-m", "test"]);
+        let credentials = Config::from_matches(&matches).unwrap().credentials.unwrap();
+        assert_eq!(credentials.user, "87654321");
+        assert_eq!(credentials.pass.expose(), "profile-key");
+    }
+
+    #[test]
+    fn test_rate_limit_file() {
+        let _env = EnvGuard::clean();
+        let matches = create_test_matches(&["send-sms", "--dry-run"]);
+        let config = Config::from_matches(&matches).unwrap();
+        if let Some(path) = config.rate_limit_file {
+            assert!(path.ends_with("send-sms/send-sms.ratelimit"));
+        }
+
+        let matches =
+            create_test_matches(&["send-sms", "--dry-run", "--rate-limit-file", "bucket"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.rate_limit_file, Some(PathBuf::from("bucket")));
+
+        let matches = create_test_matches(&["send-sms", "--dry-run", "--no-shared-rate-limit"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert!(config.rate_limit_file.is_none());
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("config.toml");
+        std::fs::write(&path, "[profiles.default]\nshared_rate_limit = false").unwrap();
+        let matches =
+            create_test_matches(&["send-sms", "--dry-run", "--config", path.to_str().unwrap()]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert!(config.rate_limit_file.is_none());
+    }
+
+    #[test]
+    fn test_emoji_table() {
+        let _env = EnvGuard::clean();
+        let matches =
+            create_test_matches(&["send-sms", "--dry-run", "--emoji-table", "emojis.toml"]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert_eq!(config.emoji_table_path, Some(PathBuf::from("emojis.toml")));
+    }
+
+    #[test]
+    fn test_probe_emojis() {
+        let _env = EnvGuard::clean();
+        let matches = create_test_matches(&[
+            "send-sms",
+            "probe-emojis",
+            "send",
+            "--range",
+            "1F300-1F3FF",
+            "--dry-run",
+            "-v",
+        ]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert!(config.dry_run);
+        assert!(config.verbose);
+        assert!(matches!(config.probe, Some(ProbeCommand::Send { .. })));
+
+        // Reports are written offline, without credentials
+        let matches = create_test_matches(&[
+            "send-sms",
+            "-u",
+            "invalid",
+            "probe-emojis",
+            "report",
+            "--rendered",
+            "1-3",
+        ]);
+        let config = Config::from_matches(&matches).unwrap();
+        assert!(config.credentials.is_none());
+        assert!(matches!(config.probe, Some(ProbeCommand::Report { .. })));
+
+        let matches = create_test_matches(&[
+            "send-sms",
+            "-m",
+            "hi",
+            "probe-emojis",
+            "report",
+            "--rendered",
+            "1",
+        ]);
+        assert!(Config::from_matches(&matches).is_err());
+    }
+
+    #[test]
+    fn test_api_url() {
+        let _env = EnvGuard::clean();
+        let matches = create_test_matches(&[
+            "send-sms",
+            "--dry-run",
+            "--api-url",
+            "http://127.0.0.1:8080/sendmsg",
+        ]);
+        let config = Config::from_matches(&matches).unwrap();
+
+        assert_eq!(
+            config.api_url.as_deref(),
+            Some("http://127.0.0.1:8080/sendmsg")
+        );
+    }
+
     #[test]
     fn test_get_config_value_with_cli_args() {
+        let _env = EnvGuard::clean();
         // Test that CLI args have priority over env vars
         let matches = Config::build_cli()
-            .try_get_matches_from(&["send-sms", "-u", "11111111", "-p", "cli-key", "-m", "test"])
+            .try_get_matches_from(["send-sms", "-u", "11111111", "-p", "cli-key", "-m", "test"])
             .unwrap();
 
         let user_result = Config::get_config_value(
@@ -328,7 +1250,7 @@ mod tests {
         // Test the logic that determines when to show interactive prompts
 
         // In test environment, prompts should be disabled
-        assert!(cfg!(test), "This test should run in test mode");
+        const { assert!(cfg!(test), "This test should run in test mode") };
 
         // Test that TTY detection works (will be false in CI/test environment)
         let _is_tty = std::io::stdin().is_terminal();
@@ -11,3 +11,23 @@ pub const MAX_MESSAGE_LENGTH: usize = 5000;
 /// Preview length for message display in verbose mode  
 /// Shows first N characters of the message for user feedback
 pub const MESSAGE_PREVIEW_LENGTH: usize = 100;
+
+/// Name of the rate limiter state file, in the per-user state directory
+/// Shared by all send-sms processes of the user so they respect a common budget
+pub const RATE_LIMIT_FILE_NAME: &str = "send-sms.ratelimit";
+
+/// Default name of the `probe-emojis` state file, in the current directory
+/// Maps the numbers of the probed emojis to the emojis themselves
+pub const PROBE_STATE_FILE_NAME: &str = "emoji-probe.json";
+
+/// Name of the send-sms directory in the user configuration directory
+/// e.g. `~/.config/send-sms` on Linux
+pub const CONFIG_DIR_NAME: &str = "send-sms";
+
+/// Name of the message templates directory, in the send-sms configuration directory
+/// Templates are looked up there by name with `--template NAME`
+pub const TEMPLATE_DIR_NAME: &str = "templates";
+
+/// Name of the configuration file, in the send-sms configuration directory
+/// Holds named profiles of credentials and default options
+pub const CONFIG_FILE_NAME: &str = "config.toml";
@@ -0,0 +1,128 @@
+use crate::input::InputHandler;
+use freemobile_api::{EncodingReport, FreeMobileError, ReplacementKind, SendPlan};
+use serde::Serialize;
+
+/// Chunk of a dry run, as printed in JSON
+#[derive(Debug, Serialize)]
+struct PlannedChunk<'a> {
+    text: &'a str,
+    graphemes: usize,
+    #[serde(flatten)]
+    encoding: EncodingReport,
+}
+
+/// Dry run output, as printed in JSON
+#[derive(Debug, Serialize)]
+struct DryRun<'a> {
+    sanitized: &'a str,
+    chunks: Vec<PlannedChunk<'a>>,
+    replacements: &'a [freemobile_api::Replacement],
+}
+
+/// Render a plan for a human reader
+pub fn render_human(plan: &SendPlan) -> String {
+    let mut output = String::from("🧪 Dry run, nothing will be sent\n");
+
+    if plan.replacements.is_empty() {
+        output.push_str("🧹 No replacement\n");
+    } else {
+        output.push_str(&format!("🧹 {} replacement(s):\n", plan.replacements.len()));
+        for replacement in &plan.replacements {
+            // Only emojis are printed as is, other replaced text is invisible or alters the terminal
+            let line = if replacement.kind == ReplacementKind::Emoji {
+                format!("  {} → {}", replacement.original, replacement.replacement)
+            } else {
+                format!(
+                    "  {} {:?} → {:?}",
+                    replacement.kind, replacement.original, replacement.replacement
+                )
+            };
+            output.push_str(&format!("{} (byte {})\n", line, replacement.byte_offset));
+        }
+    }
+
+    let encodings = plan.encodings();
+    output.push_str(&format!(
+        "📡 {}\n",
+        InputHandler::encoding_summary(&encodings)
+    ));
+
+    output.push_str(&format!("✂️ {} part(s):\n", plan.len()));
+    for ((chunk, graphemes), encoding) in plan
+        .chunks
+        .iter()
+        .zip(plan.grapheme_counts())
+        .zip(encodings)
+    {
+        output.push_str(&format!(
+            "--- {} graphemes, {} ---\n{}\n",
+            graphemes, encoding, chunk
+        ));
+    }
+
+    output
+}
+
+/// Render a plan as JSON, for scripts
+pub fn render_json(plan: &SendPlan) -> Result<String, FreeMobileError> {
+    let dry_run = DryRun {
+        sanitized: &plan.sanitized,
+        chunks: plan
+            .chunks
+            .iter()
+            .zip(plan.grapheme_counts())
+            .zip(plan.encodings())
+            .map(|((text, graphemes), encoding)| PlannedChunk {
+                text,
+                graphemes,
+                encoding,
+            })
+            .collect(),
+        replacements: &plan.replacements,
+    };
+
+    serde_json::to_string_pretty(&dry_run)
+        .map_err(|e| FreeMobileError::ConfigError(format!("Failed to serialize plan: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_human() {
+        let output = render_human(&SendPlan::for_message("Deploy 🚀 done"));
+
+        assert!(output.contains("1 replacement(s)"));
+        assert!(output.contains("🚀 → [] (byte 7)"));
+        assert!(output.contains("1 part(s)"));
+        assert!(output.contains("📡 GSM-7 with extension table, 1 SMS segment\n"));
+        assert!(output.contains(
+            "--- 14 graphemes, GSM-7 with extension table, 16 septets, 1 segment ---\nDeploy [] done\n"
+        ));
+
+        let output = render_human(&SendPlan::for_message("\x1B[1mDeploy\x1B[0m"));
+        assert!(output.contains("  ansi \"\\u{1b}[1m\" → \"\" (byte 0)\n"));
+        assert!(!output.contains('\x1B'));
+    }
+
+    #[test]
+    fn test_render_json() {
+        let plan = SendPlan::for_message(&"lorem ipsum ".repeat(100));
+        let json: serde_json::Value = serde_json::from_str(&render_json(&plan).unwrap()).unwrap();
+
+        assert_eq!(json["sanitized"], plan.sanitized.as_str());
+        assert_eq!(json["chunks"].as_array().unwrap().len(), 2);
+        assert_eq!(json["chunks"][0]["text"], plan.chunks[0].as_str());
+        assert_eq!(json["chunks"][0]["graphemes"], plan.grapheme_counts()[0]);
+        assert_eq!(json["chunks"][0]["encoding"], "gsm7-extended");
+        assert_eq!(json["chunks"][0]["segments"], 7);
+        assert!(
+            json["chunks"][0]["ucs2_chars"]
+                .as_array()
+                .unwrap()
+                .is_empty()
+        );
+        assert!(json["replacements"].as_array().unwrap().is_empty());
+    }
+}
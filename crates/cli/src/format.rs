@@ -0,0 +1,489 @@
+//! Conversion of Markdown and HTML input to compact plain text
+//!
+//! Release notes and alert bodies are often written in Markdown or HTML. Sent
+//! as is, their markup (`**`, `#`, `<p>`...) wastes characters and clutters
+//! the SMS, so they are rendered to plain text first: links become
+//! `text (url)`, list items `- item` and HTML entities are decoded.
+
+use pulldown_cmark::{Event, Options, Parser, Tag, TagEnd};
+use regex::Regex;
+use std::path::Path;
+use std::sync::LazyLock;
+
+/// HTML comments and declarations, tags and the text between them
+static HTML_TOKEN_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?s)<!--.*?-->|<[!?][^>]*>|<(/?)([a-zA-Z][a-zA-Z0-9]*)([^>]*)>|[^<]+|<")
+        .expect("Invalid HTML token regex")
+});
+
+/// `href`, `src`, `alt` or `start` attribute of an HTML tag
+static HTML_ATTRIBUTE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"(?i)\b(href|src|alt|start)\s*=\s*(?:"([^"]*)"|'([^']*)'|([^\s"'>]+))"#)
+        .expect("Invalid HTML attribute regex")
+});
+
+/// HTML elements rendered on their own lines
+const HTML_BLOCK_TAGS: &[&str] = &[
+    "address",
+    "article",
+    "aside",
+    "blockquote",
+    "dd",
+    "div",
+    "dl",
+    "dt",
+    "figcaption",
+    "figure",
+    "footer",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "header",
+    "hr",
+    "main",
+    "nav",
+    "ol",
+    "p",
+    "pre",
+    "section",
+    "table",
+    "tr",
+    "ul",
+];
+
+/// Format of the message read from the command line, a file or stdin
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputFormat {
+    /// Sent as is
+    #[default]
+    Text,
+    /// CommonMark, with tables, strikethrough and task lists
+    Markdown,
+    Html,
+}
+
+freemobile_api::named_enum!(InputFormat, "input format", {
+    Text => "text",
+    Markdown => "markdown",
+    Html => "html",
+});
+
+impl InputFormat {
+    /// Format of a file, guessed from its extension
+    pub fn from_path(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+
+        match extension.as_deref() {
+            Some("md" | "markdown" | "mdown" | "mkd") => Self::Markdown,
+            Some("html" | "htm" | "xhtml") => Self::Html,
+            _ => Self::Text,
+        }
+    }
+
+    /// Render a message to plain text
+    pub fn to_text(self, message: &str) -> String {
+        match self {
+            Self::Text => message.to_string(),
+            Self::Markdown => compact(&markdown_to_text(message)),
+            Self::Html => compact(&html_to_text(message)),
+        }
+    }
+}
+
+/// Plain text being rendered, with the state of the lists and links it is in
+#[derive(Default)]
+struct TextWriter {
+    output: String,
+    /// Next number of each ordered list, `None` for bullet lists
+    lists: Vec<Option<u64>>,
+    /// Output position and target of each link
+    links: Vec<(usize, String)>,
+    first_cell: bool,
+}
+
+impl TextWriter {
+    fn push(&mut self, text: &str) {
+        self.output.push_str(text);
+    }
+
+    fn line_break(&mut self) {
+        if !self.output.is_empty() && !self.output.ends_with('\n') {
+            self.output.push('\n');
+        }
+    }
+
+    fn block_break(&mut self) {
+        self.line_break();
+        if !self.output.is_empty() && !self.output.ends_with("\n\n") && self.lists.is_empty() {
+            self.output.push('\n');
+        }
+    }
+
+    fn start_item(&mut self) {
+        self.line_break();
+        let depth = self.lists.len().saturating_sub(1);
+        self.output.push_str(&"  ".repeat(depth));
+        let marker = match self.lists.last_mut() {
+            Some(Some(number)) => {
+                let current = *number;
+                *number = number.saturating_add(1);
+                format!("{}. ", current)
+            }
+            _ => "- ".to_string(),
+        };
+        self.output.push_str(&marker);
+    }
+
+    fn start_cell(&mut self) {
+        if !self.first_cell {
+            self.output.push_str(" | ");
+        }
+        self.first_cell = false;
+    }
+
+    fn start_link(&mut self, target: &str) {
+        self.links.push((self.output.len(), target.to_string()));
+    }
+
+    /// Append the link target, unless it is the link text itself
+    fn end_link(&mut self) {
+        if let Some((start, target)) = self.links.pop() {
+            let text = self.output[start..].trim();
+            let bare = target.strip_prefix("mailto:").unwrap_or(&target);
+            if !target.is_empty() && text != target && text != bare {
+                let target = if text.is_empty() {
+                    target
+                } else {
+                    format!(" ({})", target)
+                };
+                self.output.push_str(&target);
+            }
+        }
+    }
+}
+
+/// Render Markdown to plain text
+fn markdown_to_text(markdown: &str) -> String {
+    let options =
+        Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+    let mut writer = TextWriter::default();
+
+    for event in Parser::new_ext(markdown, options) {
+        match event {
+            Event::Start(Tag::List(start)) => {
+                writer.line_break();
+                writer.lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                writer.lists.pop();
+                writer.block_break();
+            }
+            Event::Start(Tag::Item) => writer.start_item(),
+            Event::Start(Tag::TableRow | Tag::TableHead) => {
+                writer.line_break();
+                writer.first_cell = true;
+            }
+            Event::Start(Tag::TableCell) => writer.start_cell(),
+            Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. }) => {
+                writer.start_link(&dest_url)
+            }
+            Event::End(TagEnd::Link | TagEnd::Image) => writer.end_link(),
+            Event::End(
+                TagEnd::Paragraph
+                | TagEnd::Heading(_)
+                | TagEnd::CodeBlock
+                | TagEnd::BlockQuote(_)
+                | TagEnd::HtmlBlock
+                | TagEnd::Table,
+            ) => writer.block_break(),
+            Event::End(TagEnd::Item) => writer.line_break(),
+            Event::Text(text) | Event::Code(text) => writer.push(&text),
+            Event::Html(html) | Event::InlineHtml(html) => writer.push(&html_to_text(&html)),
+            // Line breaks written by the author are kept
+            Event::SoftBreak => writer.push("\n"),
+            Event::HardBreak => writer.push("\n"),
+            Event::Rule => writer.block_break(),
+            Event::TaskListMarker(checked) => writer.push(if checked { "[x] " } else { "[ ] " }),
+            _ => {}
+        }
+    }
+
+    writer.output
+}
+
+/// Value of an attribute of an HTML tag, with its entities decoded
+fn html_attribute(attributes: &str, name: &str) -> Option<String> {
+    HTML_ATTRIBUTE_REGEX
+        .captures_iter(attributes)
+        .find(|caps| caps[1].eq_ignore_ascii_case(name))
+        .and_then(|caps| caps.get(2).or(caps.get(3)).or(caps.get(4)))
+        .map(|value| html_escape::decode_html_entities(value.as_str()).into_owned())
+}
+
+/// Render HTML to plain text
+fn html_to_text(html: &str) -> String {
+    let mut writer = TextWriter::default();
+    // Content of `<script>` and `<style>` elements is skipped up to their closing tag
+    let mut skipped: Option<String> = None;
+    let mut preformatted = false;
+
+    for caps in HTML_TOKEN_REGEX.captures_iter(html) {
+        let token = caps.get(0).expect("Match without group 0").as_str();
+        let Some(name) = caps.get(2).map(|name| name.as_str().to_ascii_lowercase()) else {
+            if token.starts_with("<!") || token.starts_with("<?") || skipped.is_some() {
+                continue;
+            }
+            let text = html_escape::decode_html_entities(token);
+            if preformatted {
+                writer.push(&text);
+            } else {
+                // Whitespace is collapsed as browsers do, keeping non-breaking spaces
+                let collapsed = text.split_ascii_whitespace().collect::<Vec<_>>().join(" ");
+                let space_before = text.starts_with(|c: char| c.is_ascii_whitespace())
+                    && !writer.output.is_empty()
+                    && !writer.output.ends_with([' ', '\n']);
+                let space_after =
+                    text.ends_with(|c: char| c.is_ascii_whitespace()) && !collapsed.is_empty();
+                if space_before {
+                    writer.push(" ");
+                }
+                writer.push(&collapsed);
+                if space_after {
+                    writer.push(" ");
+                }
+            }
+            continue;
+        };
+
+        let closing = &caps[1] == "/";
+        if let Some(ref element) = skipped {
+            if closing && *element == name {
+                skipped = None;
+            }
+            continue;
+        }
+
+        let attributes = &caps[3];
+        match (name.as_str(), closing) {
+            ("script" | "style" | "head" | "title", false) => skipped = Some(name),
+            ("br", _) => writer.push("\n"),
+            ("ul", false) => {
+                writer.line_break();
+                writer.lists.push(None);
+            }
+            ("ol", false) => {
+                writer.line_break();
+                let start = html_attribute(attributes, "start")
+                    .and_then(|start| start.parse().ok())
+                    .unwrap_or(1);
+                writer.lists.push(Some(start));
+            }
+            ("ul" | "ol", true) => {
+                writer.lists.pop();
+                writer.block_break();
+            }
+            ("li", false) => writer.start_item(),
+            ("li", true) => writer.line_break(),
+            ("tr", false) => {
+                writer.line_break();
+                writer.first_cell = true;
+            }
+            ("td" | "th", false) => writer.start_cell(),
+            ("a", false) => {
+                writer.start_link(&html_attribute(attributes, "href").unwrap_or_default())
+            }
+            ("a", true) => writer.end_link(),
+            ("img", _) => {
+                let alt = html_attribute(attributes, "alt").unwrap_or_default();
+                let src = html_attribute(attributes, "src").unwrap_or_default();
+                writer.push(&alt);
+                if !src.is_empty() {
+                    writer.push(&if alt.is_empty() {
+                        src
+                    } else {
+                        format!(" ({})", src)
+                    });
+                }
+            }
+            ("pre", closing) => {
+                preformatted = !closing;
+                writer.block_break();
+            }
+            (name, _) if HTML_BLOCK_TAGS.contains(&name) => writer.block_break(),
+            _ => {}
+        }
+    }
+
+    writer.output
+}
+
+/// Trim lines and collapse runs of blank lines
+fn compact(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut blank_lines = 0;
+
+    for line in text.lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            blank_lines += 1;
+            continue;
+        }
+        if !output.is_empty() {
+            output.push_str(if blank_lines > 0 { "\n\n" } else { "\n" });
+        }
+        output.push_str(line);
+        blank_lines = 0;
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown() {
+        let markdown = "\
+# Release 1.2.0
+
+Deploy of **send-sms** is _done_, see [the notes](https://example.com/notes).
+
+## Changes
+
+* Faster `chunking`
+* Emoji probe
+  1. send
+  2. report
+
+> Thanks to all contributors &amp; testers!
+
+---
+
+- [x] Tests
+- [ ] Docs
+
+Visit <https://example.com>";
+
+        assert_eq!(
+            InputFormat::Markdown.to_text(markdown),
+            "\
+Release 1.2.0
+
+Deploy of send-sms is done, see the notes (https://example.com/notes).
+
+Changes
+
+- Faster chunking
+- Emoji probe
+  1. send
+  2. report
+
+Thanks to all contributors & testers!
+
+- [x] Tests
+- [ ] Docs
+
+Visit https://example.com"
+        );
+    }
+
+    #[test]
+    fn test_markdown_table_and_code() {
+        let markdown = "\
+| Host | CPU |
+|------|-----|
+| web  | 93% |
+
+```
+$ uptime
+```
+
+Line one
+line two<br>three";
+
+        assert_eq!(
+            InputFormat::Markdown.to_text(markdown),
+            "Host | CPU\nweb | 93%\n\n$ uptime\n\nLine one\nline two\nthree"
+        );
+    }
+
+    #[test]
+    fn test_html() {
+        let html = r#"<!DOCTYPE html>
+<html><head><title>Alert</title><style>p { color: red; }</style></head>
+<body>
+  <h1>Disk   full</h1>
+  <!-- generated -->
+  <p>Server <b>web-01</b> is at 98%&nbsp;&mdash; see
+     <a href="https://example.com/?a=1&amp;b=2">the dashboard</a>.</p>
+  <ol start="3"><li>Clean <code>/tmp</code></li><li>Rotate logs</li></ol>
+  <ul>
+    <li>Contact <a href="mailto:ops@example.com">ops@example.com</a></li>
+  </ul>
+  <p>Line<br/>break &lt;ok&gt; <img src="graph.png" alt="graph"></p>
+  <script>alert("x")</script>
+</body></html>"#;
+
+        assert_eq!(
+            InputFormat::Html.to_text(html),
+            "\
+Disk full
+
+Server web-01 is at 98%\u{A0}— see the dashboard (https://example.com/?a=1&b=2).
+
+3. Clean /tmp
+4. Rotate logs
+
+- Contact ops@example.com
+
+Line
+break <ok> graph (graph.png)"
+        );
+
+        let html = r#"<ol start="18446744073709551615"><li>a</li><li>b</li></ol>"#;
+        assert_eq!(
+            InputFormat::Html.to_text(html),
+            "18446744073709551615. a\n18446744073709551615. b"
+        );
+    }
+
+    #[test]
+    fn test_text_unchanged() {
+        let message = "**not** <b>converted</b> &amp;";
+        assert_eq!(InputFormat::Text.to_text(message), message);
+    }
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(
+            InputFormat::from_path(Path::new("NOTES.md")),
+            InputFormat::Markdown
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("alert.HTML")),
+            InputFormat::Html
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("message.txt")),
+            InputFormat::Text
+        );
+        assert_eq!(
+            InputFormat::from_path(Path::new("message")),
+            InputFormat::Text
+        );
+    }
+
+    #[test]
+    fn test_format_names() {
+        for name in InputFormat::NAMES {
+            assert_eq!(name.parse::<InputFormat>().unwrap().to_string(), name);
+        }
+        assert!("rst".parse::<InputFormat>().is_err());
+    }
+}
@@ -1,5 +1,7 @@
 use crate::constants::{MAX_MESSAGE_LENGTH, MESSAGE_PREVIEW_LENGTH};
-use freemobile_api::FreeMobileError;
+use crate::format::InputFormat;
+use crate::template::{Template, Variables};
+use freemobile_api::{EncodingReport, FreeMobileError, Replacement, ReplacementKind};
 use inquire::Text;
 use std::fs;
 use std::io::{self, Read};
@@ -19,10 +21,7 @@ impl InputHandler {
     }
 
     pub async fn get_message_from_stdin() -> Result<String, FreeMobileError> {
-        let mut buffer = String::new();
-        io::stdin()
-            .read_to_string(&mut buffer)
-            .map_err(FreeMobileError::IoError)?;
+        let buffer = Self::read_stdin()?;
 
         if buffer.trim().is_empty() {
             return Err(FreeMobileError::EmptyMessage);
@@ -31,6 +30,41 @@ impl InputHandler {
         Ok(buffer.trim().to_string())
     }
 
+    /// Render a template, with the JSON object piped on stdin, if any, as variables
+    pub async fn get_message_from_template<P: AsRef<Path>>(
+        path: P,
+        vars: &[(String, String)],
+        exit_code: Option<i32>,
+    ) -> Result<String, FreeMobileError> {
+        let template = Template::load(path)?;
+
+        let mut variables = Variables::builtins(exit_code);
+        if Self::has_stdin_input() {
+            let json = Self::read_stdin()?;
+            if !json.trim().is_empty() {
+                variables.extend_json(&json)?;
+            }
+        }
+        for (name, value) in vars {
+            variables.insert(name.as_str(), value.as_str());
+        }
+
+        let message = template.render(&variables)?;
+        if message.trim().is_empty() {
+            return Err(FreeMobileError::EmptyMessage);
+        }
+
+        Ok(message.trim().to_string())
+    }
+
+    fn read_stdin() -> Result<String, FreeMobileError> {
+        let mut buffer = String::new();
+        io::stdin()
+            .read_to_string(&mut buffer)
+            .map_err(FreeMobileError::IoError)?;
+        Ok(buffer)
+    }
+
     pub async fn get_message_interactive() -> Result<String, FreeMobileError> {
         println!("Enter your message (press Enter to send, Ctrl+C to cancel):");
 
@@ -48,6 +82,16 @@ impl InputHandler {
         Ok(message.trim().to_string())
     }
 
+    /// Render a Markdown or HTML message to plain text, before validation and sanitization
+    pub fn convert_message(message: &str, format: InputFormat) -> Result<String, FreeMobileError> {
+        let text = format.to_text(message);
+        if text.trim().is_empty() {
+            return Err(FreeMobileError::EmptyMessage);
+        }
+
+        Ok(text)
+    }
+
     pub fn validate_message(message: &str) -> Result<(), FreeMobileError> {
         if message.trim().is_empty() {
             return Err(FreeMobileError::EmptyMessage);
@@ -89,6 +133,88 @@ impl InputHandler {
         println!();
     }
 
+    /// Warning listing the emojis replaced by the sanitizer, e.g. "2 emojis replaced: 🚀 📱"
+    pub fn replacement_warning(replacements: &[Replacement]) -> Option<String> {
+        let emojis: Vec<&str> = replacements
+            .iter()
+            .filter(|replacement| replacement.kind == ReplacementKind::Emoji)
+            .map(|replacement| replacement.original.as_str())
+            .collect();
+        if emojis.is_empty() {
+            return None;
+        }
+
+        let noun = if emojis.len() == 1 { "emoji" } else { "emojis" };
+        Some(format!(
+            "{} {} replaced: {}",
+            emojis.len(),
+            noun,
+            emojis.join(" ")
+        ))
+    }
+
+    /// Warning counting the other text cleaned up by the sanitizer,
+    /// e.g. "Cleaned up 2 ANSI escape sequences, 1 control character"
+    pub fn cleanup_warning(replacements: &[Replacement]) -> Option<String> {
+        let counts: Vec<String> = [
+            (ReplacementKind::Ansi, "ANSI escape sequence"),
+            (ReplacementKind::Control, "control character"),
+            (ReplacementKind::Joiner, "orphaned joiner"),
+            (ReplacementKind::Bidi, "bidi control"),
+            (ReplacementKind::Transliteration, "transliterated character"),
+        ]
+        .into_iter()
+        .filter_map(|(kind, noun)| {
+            let count = replacements
+                .iter()
+                .filter(|replacement| replacement.kind == kind)
+                .count();
+            match count {
+                0 => None,
+                1 => Some(format!("1 {}", noun)),
+                count => Some(format!("{} {}s", count, noun)),
+            }
+        })
+        .collect();
+
+        if counts.is_empty() {
+            None
+        } else {
+            Some(format!("Cleaned up {}", counts.join(", ")))
+        }
+    }
+
+    /// Summary of the encoding of the parts of a message,
+    /// e.g. "UCS-2, 3 SMS segments, forced by: 🚀 À"
+    pub fn encoding_summary(encodings: &[EncodingReport]) -> String {
+        let encoding = encodings
+            .iter()
+            .map(|report| report.encoding)
+            .max()
+            .map_or_else(String::new, |encoding| encoding.to_string());
+        let segments: usize = encodings.iter().map(|report| report.segments).sum();
+        let noun = if segments == 1 { "segment" } else { "segments" };
+
+        let mut forcing: Vec<&str> = Vec::new();
+        for grapheme in encodings.iter().flat_map(|report| &report.ucs2_chars) {
+            if !forcing.contains(&grapheme.as_str()) {
+                forcing.push(grapheme);
+            }
+        }
+
+        if forcing.is_empty() {
+            format!("{}, {} SMS {}", encoding, segments, noun)
+        } else {
+            format!(
+                "{}, {} SMS {}, forced by: {}",
+                encoding,
+                segments,
+                noun,
+                forcing.join(" ")
+            )
+        }
+    }
+
     pub fn has_stdin_input() -> bool {
         use is_terminal::IsTerminal;
         !io::stdin().is_terminal()
@@ -146,4 +272,52 @@ mod tests {
         let long_message = "a".repeat(150);
         InputHandler::preview_message(&long_message, true);
     }
+
+    #[test]
+    fn test_replacement_warning() {
+        let report = freemobile_api::MessageSanitizer::sanitize_with_report("Go 🚀 📱 ✅ 😀");
+        assert_eq!(
+            InputHandler::replacement_warning(&report.replacements).unwrap(),
+            "3 emojis replaced: 🚀 📱 😀"
+        );
+
+        let report = freemobile_api::MessageSanitizer::sanitize_with_report("Go 🚀");
+        assert_eq!(
+            InputHandler::replacement_warning(&report.replacements).unwrap(),
+            "1 emoji replaced: 🚀"
+        );
+        assert!(InputHandler::replacement_warning(&[]).is_none());
+
+        let report = freemobile_api::MessageSanitizer::sanitize_with_report("\x1B[1mGo\x1B[0m");
+        assert!(InputHandler::replacement_warning(&report.replacements).is_none());
+    }
+
+    #[test]
+    fn test_encoding_summary() {
+        let plan = freemobile_api::SendPlan::new(&format!("{}Ça à 🚀 Ça", "lorem ".repeat(200)));
+        assert_eq!(
+            InputHandler::encoding_summary(&plan.encodings()),
+            "UCS-2, 11 SMS segments, forced by: 🚀"
+        );
+
+        let plan = freemobile_api::SendPlan::new("Ça va");
+        assert_eq!(
+            InputHandler::encoding_summary(&plan.encodings()),
+            "GSM-7, 1 SMS segment"
+        );
+    }
+
+    #[test]
+    fn test_cleanup_warning() {
+        let report = freemobile_api::MessageSanitizer::sanitize_with_report(
+            "\x1B[31m🚀\u{200D}💻\x1B[0m\x07 done",
+        );
+        assert_eq!(
+            InputHandler::cleanup_warning(&report.replacements).unwrap(),
+            "Cleaned up 2 ANSI escape sequences, 1 control character, 1 orphaned joiner"
+        );
+
+        let report = freemobile_api::MessageSanitizer::sanitize_with_report("Go 🚀");
+        assert!(InputHandler::cleanup_warning(&report.replacements).is_none());
+    }
 }
@@ -6,7 +6,19 @@
 
 pub mod config;
 pub mod constants;
+pub mod dry_run;
+pub mod format;
 pub mod input;
+pub mod paths;
+pub mod probe;
+pub mod profile;
+pub mod resume;
+pub mod template;
 
 pub use config::Config;
+pub use format::InputFormat;
 pub use input::InputHandler;
+pub use probe::ProbeState;
+pub use profile::{ConfigFile, Profile};
+pub use resume::ResumeState;
+pub use template::Template;
@@ -1,6 +1,13 @@
-use freemobile_api::{FreeMobileClient, FreeMobileError, MessageSanitizer};
-use send_sms::{Config, InputHandler};
-use std::process;
+use freemobile_api::constants::rate_limit::{BURST, REFILL_INTERVAL_MS};
+use freemobile_api::{
+    EmojiTable, FreeMobileClient, FreeMobileError, MessageSanitizer, RateLimiter, SendPlan,
+};
+use send_sms::paths;
+use send_sms::probe::{self, ProbeCommand};
+use send_sms::{Config, InputHandler, ProbeState, ResumeState, dry_run};
+use std::path::Path;
+use std::time::Duration;
+use std::{fs, process};
 use tokio::signal;
 
 #[tokio::main]
@@ -24,34 +31,91 @@ async fn run() -> Result<(), FreeMobileError> {
 
     if config.verbose {
         println!("🚀 Starting send-sms v{}", env!("CARGO_PKG_VERSION"));
-        println!("📱 User ID: {}", mask_user_id(&config.credentials.user));
+        if let Some(ref credentials) = config.credentials {
+            println!("📱 User ID: {}", mask_user_id(&credentials.user));
+        }
     }
 
-    // Initialize FreeMobile client
-    let client = FreeMobileClient::new(config.credentials.clone())?;
+    // Probe the emojis rendered by FreeMobile instead of sending a message
+    if let Some(ref command) = config.probe {
+        return run_probe(&config, command).await;
+    }
+
+    // Resume a partially sent message instead of reading a new one
+    if let Some(ref resume_path) = config.resume_path {
+        let client = build_client(&config)?;
+        let state = ResumeState::load(resume_path)?;
+        if config.verbose {
+            println!(
+                "🔁 Resuming from part {}/{} ({} left)",
+                state.next_index + 1,
+                state.plan.len(),
+                state.remaining()
+            );
+        }
+        send_plan(&client, &state.plan, state.next_index, Some(resume_path)).await?;
+        print_success(config.verbose);
+        return Ok(());
+    }
 
     // Get message from various sources
     let message = get_message(&config).await?;
+    let message = InputHandler::convert_message(&message, config.input_format)?;
 
     // Validate original message
     InputHandler::validate_message(&message)?;
 
-    // Sanitize for sending
-    let sanitized_message = MessageSanitizer::sanitize(&message);
+    // Sanitize and split for sending
+    let emoji_table = match config.emoji_table_path {
+        Some(ref path) => EmojiTable::load(path)?,
+        None => EmojiTable::builtin(),
+    };
+    let report =
+        MessageSanitizer::sanitize_with_options(&message, &config.sanitize_options, &emoji_table);
+    let plan = SendPlan::from_sanitized(report, &config.chunk_options)?;
+    let sanitized_message = &plan.sanitized;
+
+    // Show what would be sent and stop there
+    if config.dry_run {
+        if config.json {
+            println!("{}", dry_run::render_json(&plan)?);
+        } else {
+            print!("{}", dry_run::render_human(&plan));
+        }
+        return Ok(());
+    }
 
     // Preview the message (what will actually be sent)
     let debug_mode = std::env::var("DEBUG").is_ok() || std::env::var("RUST_LOG").is_ok();
 
-    // In debug mode, show original message if it was modified
-    if debug_mode && sanitized_message != message {
-        use unicode_segmentation::UnicodeSegmentation;
-        let truncated: String = message.graphemes(true).take(50).collect();
-        println!("🐛 DEBUG - Original message: {}...", truncated);
-        println!("🐛 DEBUG - Sanitized message (what will be sent):");
+    // Warn about the emojis FreeMobile cannot render and the text cleaned up, detailed in debug mode
+    if let Some(warning) = InputHandler::replacement_warning(&plan.replacements) {
+        eprintln!("⚠️ {}", warning);
+    }
+    if let Some(warning) = InputHandler::cleanup_warning(&plan.replacements) {
+        eprintln!("🧹 {}", warning);
+    }
+    if debug_mode {
+        for replacement in &plan.replacements {
+            println!(
+                "🐛 DEBUG - {} {:?} ({}) at byte {} → {:?}",
+                replacement.kind,
+                replacement.original,
+                replacement.codepoints_label(),
+                replacement.byte_offset,
+                replacement.replacement
+            );
+        }
     }
 
     // Always show the sanitized message (what will actually be sent)
-    InputHandler::preview_message(&sanitized_message, config.verbose);
+    InputHandler::preview_message(sanitized_message, config.verbose);
+    if config.verbose {
+        println!(
+            "📡 Encoding: {}",
+            InputHandler::encoding_summary(&plan.encodings())
+        );
+    }
 
     // Send the sanitized message
     if config.verbose {
@@ -59,15 +123,145 @@ async fn run() -> Result<(), FreeMobileError> {
     }
 
     // Send the already-sanitized message
-    client.send_sanitized(&sanitized_message).await?;
+    let client = build_client(&config)?;
+    send_plan(&client, &plan, 0, None).await?;
+    print_success(config.verbose);
 
-    if config.verbose {
+    Ok(())
+}
+
+/// Initialize the FreeMobile client, sharing its rate limit with other send-sms processes
+fn build_client(config: &Config) -> Result<FreeMobileClient, FreeMobileError> {
+    let credentials = config.credentials.clone().ok_or_else(|| {
+        FreeMobileError::ConfigError("FreeMobile credentials are required".to_string())
+    })?;
+    let refill_interval = Duration::from_millis(REFILL_INTERVAL_MS);
+    let rate_limiter = match config.rate_limit_file {
+        Some(ref path) => {
+            // The limiter falls back to memory if the directory cannot be created
+            if let Some(dir) = path.parent().filter(|dir| !dir.exists()) {
+                paths::create_private_dir(dir).ok();
+            }
+            RateLimiter::file_backed(path, BURST, refill_interval)
+        }
+        None => RateLimiter::new(BURST, refill_interval),
+    };
+
+    let mut builder = FreeMobileClient::builder(credentials).rate_limiter(rate_limiter);
+    if let Some(ref api_url) = config.api_url {
+        builder = builder.base_url(api_url);
+    }
+
+    builder.build()
+}
+
+/// Send a plan from `from_index`, saving progress to a state file on partial failure
+///
+/// When resuming from `state_path`, the same file is updated on failure and
+/// removed once every part has been sent.
+async fn send_plan(
+    client: &FreeMobileClient,
+    plan: &SendPlan,
+    from_index: usize,
+    state_path: Option<&Path>,
+) -> Result<(), FreeMobileError> {
+    match client.resume(plan, from_index).await {
+        Ok(_) => {
+            if let Some(path) = state_path {
+                fs::remove_file(path).map_err(FreeMobileError::IoError)?;
+            }
+            Ok(())
+        }
+        Err(FreeMobileError::PartialSend { report, source }) => {
+            if let Some(state) = ResumeState::from_report(plan, &report) {
+                let path = match state_path {
+                    Some(path) => path.to_path_buf(),
+                    None => ResumeState::new_path()?,
+                };
+                state.save(&path)?;
+                eprintln!(
+                    "💾 {} of {} parts left, resume with: send-sms --resume {}",
+                    state.remaining(),
+                    plan.len(),
+                    path.display()
+                );
+            }
+            Err(FreeMobileError::PartialSend { report, source })
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Send numbered emoji batches, or write the allow-list of the emojis that rendered
+async fn run_probe(config: &Config, command: &ProbeCommand) -> Result<(), FreeMobileError> {
+    match command {
+        ProbeCommand::Send { source, state_path } => {
+            let state = ProbeState::new(&source.candidates()?);
+            state.save(state_path)?;
+            let noun = if state.batches.len() == 1 {
+                "message"
+            } else {
+                "messages"
+            };
+            println!(
+                "🔢 {} emojis numbered in {} {}, saved to {}",
+                state.glyph_count(),
+                state.batches.len(),
+                noun,
+                state_path.display()
+            );
+
+            if config.dry_run {
+                for batch in &state.batches {
+                    println!("\n{}", batch.message);
+                }
+                return Ok(());
+            }
+
+            // Probe messages are sent as is, unsupported emojis being what is tested
+            let client = build_client(config)?;
+            for (index, batch) in state.batches.iter().enumerate() {
+                client.send_sanitized(&batch.message).await?;
+                if config.verbose {
+                    println!("📤 Sent message {}/{}", index + 1, state.batches.len());
+                }
+            }
+            println!(
+                "✅ Probe sent, then run: send-sms probe-emojis report --state {} --rendered <NUMBERS>",
+                state_path.display()
+            );
+        }
+        ProbeCommand::Report {
+            state_path,
+            marks,
+            output,
+        } => {
+            let state = ProbeState::load(state_path)?;
+            let rendered = state.rendered(marks);
+            let allow_list = probe::render_allow_list(&rendered);
+            match output {
+                Some(path) => {
+                    fs::write(path, allow_list).map_err(FreeMobileError::IoError)?;
+                    println!(
+                        "✅ {} of {} emojis rendered, allow-list written to {}",
+                        rendered.len(),
+                        state.glyph_count(),
+                        path.display()
+                    );
+                }
+                None => print!("{}", allow_list),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_success(verbose: bool) {
+    if verbose {
         println!("✅ SMS sent successfully!");
     } else {
         println!("✅ SMS sent");
     }
-
-    Ok(())
 }
 
 async fn get_message(config: &Config) -> Result<String, FreeMobileError> {
@@ -84,7 +278,20 @@ async fn get_message(config: &Config) -> Result<String, FreeMobileError> {
         return InputHandler::get_message_from_file(file_path).await;
     }
 
-    // Priority 3: Auto-detect stdin input (pipe or redirect)
+    // Priority 3: Template, filled with variables and JSON from stdin
+    if let Some(ref template_path) = config.template_path {
+        if config.verbose {
+            println!("📝 Rendering template: {}", template_path.display());
+        }
+        return InputHandler::get_message_from_template(
+            template_path,
+            &config.template_vars,
+            config.exit_code,
+        )
+        .await;
+    }
+
+    // Priority 4: Auto-detect stdin input (pipe or redirect)
     if InputHandler::has_stdin_input() {
         if config.verbose {
             println!("📥 Detected stdin input...");
@@ -92,7 +299,7 @@ async fn get_message(config: &Config) -> Result<String, FreeMobileError> {
         return InputHandler::get_message_from_stdin().await;
     }
 
-    // Priority 4: Interactive mode (default fallback)
+    // Priority 5: Interactive mode (default fallback)
     if config.verbose {
         println!("💬 No input detected, using interactive mode...");
     }
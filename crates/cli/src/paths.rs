@@ -0,0 +1,48 @@
+//! Per-user directory of the files shared between send-sms runs
+//!
+//! The rate limiter bucket and resume state files live in a directory only
+//! readable by its owner, instead of the world-writable temporary directory
+//! where other local users could read them or plant symbolic links.
+
+use crate::constants::CONFIG_DIR_NAME;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Per-user state directory, e.g. `$XDG_RUNTIME_DIR/send-sms` or `~/.cache/send-sms`
+pub fn state_dir() -> Option<PathBuf> {
+    dirs::runtime_dir()
+        .or_else(dirs::cache_dir)
+        .map(|dir| dir.join(CONFIG_DIR_NAME))
+}
+
+/// Create a directory and its missing parents, readable by their owner only
+pub fn create_private_dir(path: &Path) -> io::Result<()> {
+    let mut builder = fs::DirBuilder::new();
+    builder.recursive(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::DirBuilderExt;
+        builder.mode(0o700);
+    }
+    builder.create(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_create_private_dir() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let dir = tmp.path().join("state").join("send-sms");
+        create_private_dir(&dir).unwrap();
+        create_private_dir(&dir).unwrap();
+
+        let mode = fs::metadata(&dir).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+    }
+}
@@ -0,0 +1,470 @@
+//! `probe-emojis` subcommand, testing which emojis FreeMobile renders
+//!
+//! Probing is done in two steps:
+//!
+//! 1. `send-sms probe-emojis send` numbers the candidate emojis and sends them
+//!    in batches fitting a single SMS, saving the number of each emoji to a
+//!    state file.
+//! 2. `send-sms probe-emojis report` reads the numbers of the emojis that
+//!    rendered (or failed to) on the phone and writes an allow-list, loadable
+//!    with `--emoji-table`.
+
+use crate::constants::PROBE_STATE_FILE_NAME;
+use clap::{Arg, ArgGroup, ArgMatches, Command};
+use freemobile_api::FreeMobileError;
+use freemobile_api::constants::MAX_MESSAGE_LENGTH;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Step of the `probe-emojis` subcommand
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProbeCommand {
+    /// Send the candidate emojis in numbered batches
+    Send {
+        source: CandidateSource,
+        state_path: PathBuf,
+    },
+    /// Write the allow-list of the emojis marked as rendered
+    Report {
+        state_path: PathBuf,
+        marks: Marks,
+        output: Option<PathBuf>,
+    },
+}
+
+/// Where candidate emojis come from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CandidateSource {
+    /// Every emoji of a range of code points
+    Range(RangeInclusive<u32>),
+    /// Emojis separated by whitespace in a file
+    File(PathBuf),
+}
+
+/// Numbers of the probed emojis, as marked by the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Marks {
+    /// The listed emojis rendered, the others did not
+    Rendered(NumberList),
+    /// The listed emojis did not render, the others did
+    Failed(NumberList),
+}
+
+/// List of numbers and ranges, e.g. `1-20,25,30-32`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NumberList(Vec<RangeInclusive<usize>>);
+
+impl NumberList {
+    pub fn parse(list: &str) -> Result<Self, FreeMobileError> {
+        let invalid = || FreeMobileError::ConfigError(format!("Invalid number list '{}'", list));
+
+        list.split(',')
+            .map(|item| {
+                let (start, end) = item.trim().split_once('-').unwrap_or((item, item));
+                let start = start.trim().parse::<usize>().map_err(|_| invalid())?;
+                let end = end.trim().parse::<usize>().map_err(|_| invalid())?;
+                if start > end {
+                    return Err(invalid());
+                }
+                Ok(start..=end)
+            })
+            .collect::<Result<_, _>>()
+            .map(Self)
+    }
+
+    pub fn contains(&self, number: usize) -> bool {
+        self.0.iter().any(|range| range.contains(&number))
+    }
+}
+
+/// Emoji of a probe and its number in the probe messages
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProbeGlyph {
+    pub number: usize,
+    pub emoji: String,
+}
+
+/// Message of a probe and the emojis it holds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProbeBatch {
+    pub message: String,
+    pub glyphs: Vec<ProbeGlyph>,
+}
+
+/// Batches of a probe, saved between sending them and reporting the results
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProbeState {
+    pub batches: Vec<ProbeBatch>,
+}
+
+impl ProbeCommand {
+    /// Definition of the `probe-emojis` subcommand
+    pub fn command() -> Command {
+        Command::new("probe-emojis")
+            .about("Test which emojis FreeMobile renders and generate an allow-list")
+            .subcommand_required(true)
+            .subcommand(
+                Command::new("send")
+                    .about("Send candidate emojis in numbered messages")
+                    .arg(
+                        Arg::new("range")
+                            .long("range")
+                            .value_name("START-END")
+                            .help("Code point range of the candidates, e.g. 1F300-1F5FF"),
+                    )
+                    .arg(
+                        Arg::new("candidates")
+                            .long("candidates")
+                            .value_name("PATH")
+                            .help("File of candidates separated by whitespace"),
+                    )
+                    .group(
+                        ArgGroup::new("source")
+                            .args(["range", "candidates"])
+                            .required(true),
+                    )
+                    .arg(Self::state_arg()),
+            )
+            .subcommand(
+                Command::new("report")
+                    .about("Write the allow-list of the emojis that rendered")
+                    .arg(
+                        Arg::new("rendered")
+                            .long("rendered")
+                            .value_name("NUMBERS")
+                            .help("Numbers of the emojis that rendered, e.g. 1-20,25"),
+                    )
+                    .arg(
+                        Arg::new("failed")
+                            .long("failed")
+                            .value_name("NUMBERS")
+                            .help("Numbers of the emojis that did not render, e.g. 21-24"),
+                    )
+                    .group(
+                        ArgGroup::new("marks")
+                            .args(["rendered", "failed"])
+                            .required(true),
+                    )
+                    .arg(
+                        Arg::new("output")
+                            .short('o')
+                            .long("output")
+                            .value_name("PATH")
+                            .help("Allow-list file to write [default: stdout]"),
+                    )
+                    .arg(Self::state_arg()),
+            )
+    }
+
+    fn state_arg() -> Arg {
+        Arg::new("state")
+            .long("state")
+            .value_name("PATH")
+            .help(format!(
+                "Probe state file [default: {}]",
+                PROBE_STATE_FILE_NAME
+            ))
+    }
+
+    /// Parse the arguments of the `probe-emojis` subcommand
+    pub fn from_matches(matches: &ArgMatches) -> Result<Self, FreeMobileError> {
+        let state_path = |matches: &ArgMatches| {
+            matches
+                .get_one::<String>("state")
+                .map_or_else(|| PathBuf::from(PROBE_STATE_FILE_NAME), PathBuf::from)
+        };
+
+        match matches.subcommand() {
+            Some(("send", matches)) => {
+                let source = match matches.get_one::<String>("range") {
+                    Some(range) => CandidateSource::Range(parse_range(range)?),
+                    None => CandidateSource::File(
+                        matches
+                            .get_one::<String>("candidates")
+                            .map(PathBuf::from)
+                            .unwrap_or_default(),
+                    ),
+                };
+                Ok(Self::Send {
+                    source,
+                    state_path: state_path(matches),
+                })
+            }
+            Some(("report", matches)) => {
+                let marks = match matches.get_one::<String>("rendered") {
+                    Some(list) => Marks::Rendered(NumberList::parse(list)?),
+                    None => Marks::Failed(NumberList::parse(
+                        matches
+                            .get_one::<String>("failed")
+                            .map_or("", String::as_str),
+                    )?),
+                };
+                Ok(Self::Report {
+                    state_path: state_path(matches),
+                    marks,
+                    output: matches.get_one::<String>("output").map(PathBuf::from),
+                })
+            }
+            _ => Err(FreeMobileError::ConfigError(
+                "Missing probe-emojis step, expected send or report".to_string(),
+            )),
+        }
+    }
+}
+
+/// Parse a code point range such as `1F300-1F5FF` or `U+1F300-U+1F5FF`
+fn parse_range(range: &str) -> Result<RangeInclusive<u32>, FreeMobileError> {
+    let invalid = || FreeMobileError::ConfigError(format!("Invalid code point range '{}'", range));
+    let codepoint = |value: &str| {
+        let value = value.trim();
+        let hex = value
+            .strip_prefix("U+")
+            .or_else(|| value.strip_prefix("u+"))
+            .unwrap_or(value);
+        u32::from_str_radix(hex, 16).map_err(|_| invalid())
+    };
+
+    let (start, end) = range.split_once('-').ok_or_else(invalid)?;
+    let (start, end) = (codepoint(start)?, codepoint(end)?);
+    if start > end || end > char::MAX as u32 {
+        return Err(invalid());
+    }
+    Ok(start..=end)
+}
+
+impl CandidateSource {
+    /// Candidate emojis, without duplicates
+    pub fn candidates(&self) -> Result<Vec<String>, FreeMobileError> {
+        let candidates: Vec<String> = match self {
+            // Emojis are probed in their fully-qualified form, e.g. with U+FE0F
+            Self::Range(range) => range
+                .clone()
+                .filter_map(char::from_u32)
+                .filter_map(|c| emojis::get(&c.to_string()))
+                .map(|emoji| emoji.as_str().to_string())
+                .collect(),
+            Self::File(path) => fs::read_to_string(path)
+                .map_err(FreeMobileError::IoError)?
+                .split_whitespace()
+                .map(str::to_string)
+                .collect(),
+        };
+
+        let mut seen = HashSet::new();
+        let candidates: Vec<String> = candidates
+            .into_iter()
+            .filter(|candidate| seen.insert(candidate.clone()))
+            .collect();
+        if candidates.is_empty() {
+            return Err(FreeMobileError::ConfigError(
+                "No candidate emoji to probe".to_string(),
+            ));
+        }
+        Ok(candidates)
+    }
+}
+
+impl ProbeState {
+    /// Number the candidates and group them in messages fitting a single SMS
+    ///
+    /// Each message starts with an `Emoji probe i/n` header followed by
+    /// `number:emoji` entries separated by spaces.
+    pub fn new(candidates: &[String]) -> Self {
+        let header_width = |total: usize| format!("Emoji probe {0}/{0}", total).len();
+        // Upper bound of the header, as there are never more batches than candidates
+        let room = MAX_MESSAGE_LENGTH - header_width(candidates.len());
+
+        let mut batches: Vec<Vec<ProbeGlyph>> = Vec::new();
+        let mut length = room;
+        for (index, emoji) in candidates.iter().enumerate() {
+            let number = index + 1;
+            let entry_length = format!(" {}:{}", number, emoji).graphemes(true).count();
+            if length + entry_length > room {
+                batches.push(Vec::new());
+                length = 0;
+            }
+            length += entry_length;
+            batches
+                .last_mut()
+                .expect("Batch pushed above")
+                .push(ProbeGlyph {
+                    number,
+                    emoji: emoji.clone(),
+                });
+        }
+
+        let count = batches.len();
+        let batches = batches
+            .into_iter()
+            .enumerate()
+            .map(|(index, glyphs)| {
+                let mut message = format!("Emoji probe {}/{}", index + 1, count);
+                for glyph in &glyphs {
+                    message.push_str(&format!(" {}:{}", glyph.number, glyph.emoji));
+                }
+                ProbeBatch { message, glyphs }
+            })
+            .collect();
+
+        Self { batches }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, FreeMobileError> {
+        let content = fs::read_to_string(path).map_err(FreeMobileError::IoError)?;
+        serde_json::from_str(&content)
+            .map_err(|e| FreeMobileError::ConfigError(format!("Invalid probe state file: {}", e)))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), FreeMobileError> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| {
+            FreeMobileError::ConfigError(format!("Failed to serialize probe state: {}", e))
+        })?;
+        fs::write(path, content).map_err(FreeMobileError::IoError)
+    }
+
+    /// Total number of probed emojis
+    pub fn glyph_count(&self) -> usize {
+        self.batches.iter().map(|batch| batch.glyphs.len()).sum()
+    }
+
+    /// Emojis that rendered according to `marks`, in probe order
+    pub fn rendered(&self, marks: &Marks) -> Vec<&str> {
+        self.batches
+            .iter()
+            .flat_map(|batch| &batch.glyphs)
+            .filter(|glyph| match marks {
+                Marks::Rendered(list) => list.contains(glyph.number),
+                Marks::Failed(list) => !list.contains(glyph.number),
+            })
+            .map(|glyph| glyph.emoji.as_str())
+            .collect()
+    }
+}
+
+/// Emoji table file declaring `emojis` as supported
+pub fn render_allow_list(emojis: &[&str]) -> String {
+    let mut output = String::from("# Generated by send-sms probe-emojis\nsupported = [\n");
+    for emoji in emojis {
+        // JSON string escapes are valid in TOML basic strings
+        let quoted = serde_json::to_string(emoji).expect("Strings always serialize");
+        output.push_str(&format!("    {},\n", quoted));
+    }
+    output.push_str("]\n");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use freemobile_api::EmojiTable;
+
+    fn parse(args: &[&str]) -> Result<ProbeCommand, FreeMobileError> {
+        let matches = ProbeCommand::command()
+            .try_get_matches_from(args)
+            .map_err(|e| FreeMobileError::ConfigError(e.to_string()))?;
+        ProbeCommand::from_matches(&matches)
+    }
+
+    #[test]
+    fn test_parse_send() {
+        assert_eq!(
+            parse(&["probe-emojis", "send", "--range", "U+1F300-1F30F"]).unwrap(),
+            ProbeCommand::Send {
+                source: CandidateSource::Range(0x1F300..=0x1F30F),
+                state_path: PathBuf::from(PROBE_STATE_FILE_NAME),
+            }
+        );
+        assert!(parse(&["probe-emojis", "send"]).is_err());
+        assert!(parse(&["probe-emojis", "send", "--range", "1F30F-1F300"]).is_err());
+        assert!(parse(&["probe-emojis", "send", "--range", "zz-1F300"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_report() {
+        let command = parse(&[
+            "probe-emojis",
+            "report",
+            "--failed",
+            "3,5-6",
+            "--state",
+            "probe.json",
+        ])
+        .unwrap();
+        assert_eq!(
+            command,
+            ProbeCommand::Report {
+                state_path: PathBuf::from("probe.json"),
+                marks: Marks::Failed(NumberList(vec![3..=3, 5..=6])),
+                output: None,
+            }
+        );
+        assert!(parse(&["probe-emojis", "report"]).is_err());
+        assert!(parse(&["probe-emojis", "report", "--rendered", "4-2"]).is_err());
+    }
+
+    #[test]
+    fn test_range_candidates() {
+        let candidates = CandidateSource::Range(0x2600..=0x2603)
+            .candidates()
+            .unwrap();
+        assert_eq!(candidates, vec!["☀️", "☁️", "☂️", "☃️"]);
+
+        assert!(CandidateSource::Range(0x41..=0x5A).candidates().is_err());
+    }
+
+    #[test]
+    fn test_batches_fit_a_single_sms() {
+        let candidates = CandidateSource::Range(0x1F300..=0x1F5FF)
+            .candidates()
+            .unwrap();
+        let state = ProbeState::new(&candidates);
+
+        assert!(state.batches.len() > 1);
+        assert_eq!(state.glyph_count(), candidates.len());
+        for (index, batch) in state.batches.iter().enumerate() {
+            assert!(batch.message.graphemes(true).count() <= MAX_MESSAGE_LENGTH);
+            assert!(batch.message.starts_with(&format!(
+                "Emoji probe {}/{} ",
+                index + 1,
+                state.batches.len()
+            )));
+        }
+        assert_eq!(state.batches[0].glyphs[0].number, 1);
+        assert!(state.batches[0].message.contains(" 1:🌀 2:🌁 "));
+    }
+
+    #[test]
+    fn test_rendered_allow_list() {
+        let candidates: Vec<String> = ["🫠", "🪿", "🦀", "🚀"].map(String::from).to_vec();
+        let state = ProbeState::new(&candidates);
+
+        let rendered = NumberList::parse("1-2,4").unwrap();
+        assert_eq!(
+            state.rendered(&Marks::Rendered(rendered)),
+            vec!["🫠", "🪿", "🚀"]
+        );
+        let failed = NumberList::parse("1,3").unwrap();
+        let emojis = state.rendered(&Marks::Failed(failed));
+        assert_eq!(emojis, vec!["🪿", "🚀"]);
+
+        let table = EmojiTable::builtin()
+            .merge_toml(&render_allow_list(&emojis))
+            .unwrap();
+        assert!(table.is_supported("🪿"));
+        assert!(!table.is_supported("🫠"));
+    }
+
+    #[test]
+    fn test_state_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("probe.json");
+        let state = ProbeState::new(&["🫠".to_string()]);
+
+        state.save(&path).unwrap();
+        assert_eq!(ProbeState::load(&path).unwrap(), state);
+    }
+}
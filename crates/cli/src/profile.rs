@@ -0,0 +1,326 @@
+//! Configuration file with named profiles
+//!
+//! The configuration file (`~/.config/send-sms/config.toml` on Linux) holds
+//! credentials and default options, grouped in named profiles:
+//!
+//! ```toml
+//! default_profile = "home"
+//!
+//! [profiles.home]
+//! user = "12345678"
+//! pass_file = "/etc/send-sms/home.key"
+//! emoji_policy = "closest"
+//!
+//! [profiles.ops]
+//! user = "87654321"
+//! pass_command = "pass show freemobile/ops"
+//! max_parts = 2
+//! ```
+//!
+//! Profile values are only used when neither the command line nor the
+//! environment set them.
+
+use crate::constants::{CONFIG_DIR_NAME, CONFIG_FILE_NAME};
+use freemobile_api::{ApiKey, FreeMobileError};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use zeroize::Zeroizing;
+
+/// Name of the profile used when the file has no `default_profile`
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Content of the configuration file
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConfigFile {
+    /// Profile used without `--profile`, [`DEFAULT_PROFILE`] if not set
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// Credentials and default options of a profile
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    pub user: Option<String>,
+    /// API key, in clear in the file but redacted in `Debug` output
+    pub pass: Option<ApiKey>,
+    /// File whose first line is the API key
+    pub pass_file: Option<PathBuf>,
+    /// Shell command printing the API key, e.g. a password manager
+    pub pass_command: Option<String>,
+    pub api_url: Option<String>,
+    pub max_parts: Option<u64>,
+    pub overflow: Option<String>,
+    pub split: Option<String>,
+    pub emoji_policy: Option<String>,
+    pub emoji_placeholder: Option<String>,
+    pub emoji_table: Option<PathBuf>,
+    pub control_chars: Option<String>,
+    pub gsm7: Option<bool>,
+    pub template_dir: Option<PathBuf>,
+    pub rate_limit_file: Option<PathBuf>,
+    /// `false` to keep the rate limit of each run in memory
+    pub shared_rate_limit: Option<bool>,
+}
+
+impl ConfigFile {
+    /// Default path of the configuration file, e.g. `~/.config/send-sms/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        dirs::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, FreeMobileError> {
+        let path = path.as_ref();
+        let content = fs::read_to_string(path).map_err(|e| {
+            FreeMobileError::ConfigError(format!(
+                "Failed to read config file {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::parse(&content).map_err(|e| match e {
+            FreeMobileError::ConfigError(message) => {
+                FreeMobileError::ConfigError(format!("{}: {}", path.display(), message))
+            }
+            e => e,
+        })
+    }
+
+    pub fn parse(content: &str) -> Result<Self, FreeMobileError> {
+        let file: Self = toml::from_str(content).map_err(|e| {
+            FreeMobileError::ConfigError(format!("Invalid config file: {}", e.message()))
+        })?;
+
+        if let Some(ref name) = file.default_profile
+            && !file.profiles.contains_key(name)
+        {
+            return Err(FreeMobileError::ConfigError(format!(
+                "Unknown default profile '{}'",
+                name
+            )));
+        }
+        for (name, profile) in &file.profiles {
+            profile.validate().map_err(|message| {
+                FreeMobileError::ConfigError(format!("Profile '{}': {}", name, message))
+            })?;
+        }
+
+        Ok(file)
+    }
+
+    /// Profile selected by name, or the default one
+    ///
+    /// Without a name, the `default_profile` is used, then the profile named
+    /// [`DEFAULT_PROFILE`] if any. A file without profiles selects none.
+    pub fn profile(&self, name: Option<&str>) -> Result<Option<&Profile>, FreeMobileError> {
+        match name.or(self.default_profile.as_deref()) {
+            Some(name) => self.profiles.get(name).map(Some).ok_or_else(|| {
+                FreeMobileError::ConfigError(format!(
+                    "Unknown profile '{}', expected one of: {}",
+                    name,
+                    self.profiles.keys().cloned().collect::<Vec<_>>().join(", ")
+                ))
+            }),
+            None => Ok(self.profiles.get(DEFAULT_PROFILE)),
+        }
+    }
+}
+
+impl Profile {
+    fn validate(&self) -> Result<(), String> {
+        let key_sources = [
+            self.pass.is_some(),
+            self.pass_file.is_some(),
+            self.pass_command.is_some(),
+        ];
+        if key_sources.into_iter().filter(|&set| set).count() > 1 {
+            return Err("only one of pass, pass_file and pass_command can be set".to_string());
+        }
+        if self.max_parts == Some(0) {
+            return Err("max_parts must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// API key of the profile, read from its file or command output if needed
+    ///
+    /// The file content and command output are wiped from memory once the key
+    /// has been extracted.
+    pub fn api_key(&self) -> Result<Option<ApiKey>, FreeMobileError> {
+        if let Some(ref path) = self.pass_file {
+            let content = Zeroizing::new(fs::read_to_string(path).map_err(|e| {
+                FreeMobileError::ConfigError(format!(
+                    "Failed to read API key file {}: {}",
+                    path.display(),
+                    e
+                ))
+            })?);
+            return Ok(first_line(&content));
+        }
+
+        if let Some(ref command) = self.pass_command {
+            let output = shell_command(command).output().map_err(|e| {
+                FreeMobileError::ConfigError(format!(
+                    "Failed to run pass_command '{}': {}",
+                    command, e
+                ))
+            })?;
+            if !output.status.success() {
+                return Err(FreeMobileError::ConfigError(format!(
+                    "pass_command '{}' failed with {}",
+                    command, output.status
+                )));
+            }
+            let stdout = Zeroizing::new(output.stdout);
+            let stdout = std::str::from_utf8(&stdout).map_err(|_| {
+                FreeMobileError::ConfigError(format!(
+                    "pass_command '{}' printed invalid UTF-8",
+                    command
+                ))
+            })?;
+            return Ok(first_line(stdout));
+        }
+
+        Ok(self.pass.clone())
+    }
+}
+
+fn first_line(content: &str) -> Option<ApiKey> {
+    content
+        .lines()
+        .next()
+        .map(|key| ApiKey::new(key.trim().to_string()))
+}
+
+fn shell_command(command: &str) -> process::Command {
+    let mut shell = if cfg!(windows) {
+        let mut shell = process::Command::new("cmd");
+        shell.arg("/C");
+        shell
+    } else {
+        let mut shell = process::Command::new("sh");
+        shell.arg("-c");
+        shell
+    };
+    shell.arg(command).stdin(process::Stdio::null());
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const CONFIG: &str = r#"
+default_profile = "home"
+
+[profiles.home]
+user = "12345678"
+pass = "home-key"
+emoji_policy = "closest"
+
+[profiles.ops]
+user = "87654321"
+pass_command = "echo ops-key"
+max_parts = 2
+gsm7 = true
+"#;
+
+    #[test]
+    fn test_profile_selection() {
+        let file = ConfigFile::parse(CONFIG).unwrap();
+
+        let home = file.profile(None).unwrap().unwrap();
+        assert_eq!(home.user.as_deref(), Some("12345678"));
+        assert_eq!(home.emoji_policy.as_deref(), Some("closest"));
+
+        let ops = file.profile(Some("ops")).unwrap().unwrap();
+        assert_eq!(ops.max_parts, Some(2));
+        assert_eq!(ops.gsm7, Some(true));
+
+        let error = file.profile(Some("work")).unwrap_err().to_string();
+        assert!(error.contains("Unknown profile 'work', expected one of: home, ops"));
+    }
+
+    #[test]
+    fn test_default_profile() {
+        let file = ConfigFile::parse("[profiles.default]\nuser = \"12345678\"").unwrap();
+        assert!(file.profile(None).unwrap().is_some());
+
+        let file = ConfigFile::parse("[profiles.other]\nuser = \"12345678\"").unwrap();
+        assert!(file.profile(None).unwrap().is_none());
+
+        assert!(
+            ConfigFile::parse("")
+                .unwrap()
+                .profile(None)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_invalid_files() {
+        assert!(ConfigFile::parse("default_profile = \"missing\"").is_err());
+        assert!(ConfigFile::parse("[profiles.home]\nunknown = 1").is_err());
+        assert!(ConfigFile::parse("[profiles.home]\nmax_parts = 0").is_err());
+        assert!(
+            ConfigFile::parse("[profiles.home]\npass = \"a\"\npass_command = \"echo b\"").is_err()
+        );
+    }
+
+    #[test]
+    fn test_api_key_sources() {
+        let tmp = TempDir::new().unwrap();
+        let key_file = tmp.path().join("key");
+        fs::write(&key_file, "file-key\n").unwrap();
+
+        let profile = Profile {
+            pass_file: Some(key_file),
+            ..Profile::default()
+        };
+        assert_eq!(
+            profile.api_key().unwrap().as_ref().map(ApiKey::expose),
+            Some("file-key")
+        );
+
+        let file = ConfigFile::parse(CONFIG).unwrap();
+        let ops = file.profile(Some("ops")).unwrap().unwrap();
+        assert_eq!(
+            ops.api_key().unwrap().as_ref().map(ApiKey::expose),
+            Some("ops-key")
+        );
+
+        let profile = Profile {
+            pass_command: Some("exit 3".to_string()),
+            ..Profile::default()
+        };
+        assert!(profile.api_key().is_err());
+
+        let file = ConfigFile::parse(CONFIG).unwrap();
+        let home = file.profile(Some("home")).unwrap().unwrap();
+        assert_eq!(
+            home.api_key().unwrap().as_ref().map(ApiKey::expose),
+            Some("home-key")
+        );
+        assert!(!format!("{:?}", home).contains("home-key"));
+
+        assert!(Profile::default().api_key().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_load() {
+        let tmp = TempDir::new().unwrap();
+        let path = tmp.path().join("config.toml");
+        fs::write(&path, "[profiles.home]\nuser = 12345678").unwrap();
+
+        let error = ConfigFile::load(&path).unwrap_err().to_string();
+        assert!(error.contains("config.toml"));
+        assert!(ConfigFile::load(tmp.path().join("missing.toml")).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+use crate::paths;
+use freemobile_api::{FreeMobileError, SendPlan, SendReport};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Progress of a partially sent message, saved to resume it later
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumeState {
+    pub plan: SendPlan,
+    pub next_index: usize,
+}
+
+impl ResumeState {
+    /// Build the state of a failed send from its report
+    ///
+    /// `plan` is the full plan of the message, as the report of a resumed
+    /// send only lists the chunks it attempted.
+    pub fn from_report(plan: &SendPlan, report: &SendReport) -> Option<Self> {
+        report.first_unsent_index().map(|next_index| Self {
+            plan: plan.clone(),
+            next_index,
+        })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, FreeMobileError> {
+        let content = fs::read_to_string(path).map_err(FreeMobileError::IoError)?;
+        let state: Self = serde_json::from_str(&content).map_err(|e| {
+            FreeMobileError::ConfigError(format!("Invalid resume state file: {}", e))
+        })?;
+
+        if state.next_index >= state.plan.len() {
+            return Err(FreeMobileError::ConfigError(
+                "Resume state file has no chunk left to send".to_string(),
+            ));
+        }
+
+        Ok(state)
+    }
+
+    /// Save the state, in a file only readable by its owner as it holds the message
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), FreeMobileError> {
+        let content = serde_json::to_string_pretty(self).map_err(|e| {
+            FreeMobileError::ConfigError(format!("Failed to serialize resume state: {}", e))
+        })?;
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let mut file = options.open(path)?;
+        file.write_all(content.as_bytes())?;
+        Ok(())
+    }
+
+    /// Number of chunks left to send
+    pub fn remaining(&self) -> usize {
+        self.plan.len().saturating_sub(self.next_index)
+    }
+
+    /// New state file path in the per-user state directory, created if needed
+    pub fn new_path() -> Result<PathBuf, FreeMobileError> {
+        let dir = paths::state_dir().ok_or_else(|| {
+            FreeMobileError::ConfigError(
+                "No state directory to save the resume state file".to_string(),
+            )
+        })?;
+        paths::create_private_dir(&dir)?;
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        Ok(dir.join(format!(
+            "send-sms-resume-{}-{}.json",
+            timestamp,
+            std::process::id()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use freemobile_api::{ChunkOutcome, ChunkReport};
+    use std::time::Duration;
+    use tempfile::NamedTempFile;
+
+    fn three_chunk_plan() -> SendPlan {
+        SendPlan {
+            chunks: vec![
+                "[1/3] first".to_string(),
+                "[2/3] second".to_string(),
+                "[3/3] third".to_string(),
+            ],
+            ..SendPlan::default()
+        }
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let state = ResumeState {
+            plan: three_chunk_plan(),
+            next_index: 1,
+        };
+        let file = NamedTempFile::new().unwrap();
+
+        state.save(file.path()).unwrap();
+        let loaded = ResumeState::load(file.path()).unwrap();
+
+        assert_eq!(loaded, state);
+        assert_eq!(loaded.remaining(), 2);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_save_is_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let tmp = tempfile::TempDir::new().unwrap();
+        let path = tmp.path().join("state.json");
+        let state = ResumeState {
+            plan: three_chunk_plan(),
+            next_index: 1,
+        };
+        state.save(&path).unwrap();
+
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    #[test]
+    fn test_load_invalid_file() {
+        let file = NamedTempFile::new().unwrap();
+        fs::write(file.path(), "not json").unwrap();
+
+        let result = ResumeState::load(file.path());
+        assert!(matches!(result, Err(FreeMobileError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_load_completed_state() {
+        let state = ResumeState {
+            plan: three_chunk_plan(),
+            next_index: 3,
+        };
+        let file = NamedTempFile::new().unwrap();
+        state.save(file.path()).unwrap();
+
+        assert!(ResumeState::load(file.path()).is_err());
+    }
+
+    #[test]
+    fn test_from_report() {
+        let plan = three_chunk_plan();
+        let report = SendReport {
+            chunks: vec![
+                ChunkReport {
+                    status: Some(200),
+                    attempts: 1,
+                    outcome: ChunkOutcome::Sent,
+                    ..not_sent(1, "[2/3] second")
+                },
+                not_sent(2, "[3/3] third"),
+            ],
+        };
+
+        let state = ResumeState::from_report(&plan, &report).unwrap();
+        assert_eq!(state.next_index, 2);
+        assert_eq!(state.plan, plan);
+    }
+
+    fn not_sent(index: usize, text: &str) -> ChunkReport {
+        ChunkReport {
+            index,
+            text: text.to_string(),
+            status: None,
+            body: None,
+            latency: Duration::ZERO,
+            attempts: 0,
+            outcome: ChunkOutcome::NotSent,
+        }
+    }
+}
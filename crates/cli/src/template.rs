@@ -0,0 +1,439 @@
+//! Message templates with `{variable}` placeholders
+//!
+//! A template is a text file in which every `{name}` placeholder is replaced
+//! by the value of a variable. Values come, by decreasing priority, from
+//! `--var KEY=VALUE`, a JSON object piped on stdin, the built-in variables and
+//! the environment. `{{` and `}}` write literal braces.
+//!
+//! ```text
+//! {hostname}: backup failed with code {exit_code} on {date} at {time}
+//! ```
+
+use crate::constants::{CONFIG_DIR_NAME, TEMPLATE_DIR_NAME};
+use freemobile_api::FreeMobileError;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Literal(String),
+    Variable(String),
+}
+
+/// Parsed message template
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    segments: Vec<Segment>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, FreeMobileError> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut rest = source;
+
+        while let Some(index) = rest.find(['{', '}']) {
+            literal.push_str(&rest[..index]);
+            let tail = &rest[index..];
+
+            if let Some(after) = tail.strip_prefix("{{").or(tail.strip_prefix("}}")) {
+                literal.push_str(&tail[..1]);
+                rest = after;
+            } else if let Some(after) = tail.strip_prefix('{') {
+                let end = after.find('}').ok_or_else(|| {
+                    FreeMobileError::ConfigError(format!(
+                        "Unclosed template placeholder at byte {}, use {{{{ for a literal brace",
+                        source.len() - tail.len()
+                    ))
+                })?;
+                let name = after[..end].trim();
+                if !is_variable_name(name) {
+                    return Err(FreeMobileError::ConfigError(format!(
+                        "Invalid template variable name '{}'",
+                        name
+                    )));
+                }
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                }
+                segments.push(Segment::Variable(name.to_string()));
+                rest = &after[end + 1..];
+            } else {
+                // A lone closing brace is kept as is
+                literal.push('}');
+                rest = &tail[1..];
+            }
+        }
+
+        literal.push_str(rest);
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Self { segments })
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, FreeMobileError> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path).map_err(|e| {
+            FreeMobileError::ConfigError(format!(
+                "Failed to read template {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        Self::parse(source.trim())
+    }
+
+    /// Path of a template given by path, or by name in the template directory
+    ///
+    /// A name matches a file of the directory with or without extension, e.g.
+    /// `disk-full` matches `disk-full.md`.
+    pub fn find(name_or_path: &str, dir: Option<&Path>) -> Result<PathBuf, FreeMobileError> {
+        let path = Path::new(name_or_path);
+        if path.is_file() || path.components().count() > 1 {
+            return Ok(path.to_path_buf());
+        }
+
+        let dir = dir
+            .map(Path::to_path_buf)
+            .or_else(default_dir)
+            .ok_or_else(|| {
+                FreeMobileError::ConfigError(
+                    "No configuration directory found for templates, use --template-dir"
+                        .to_string(),
+                )
+            })?;
+        let not_found = || {
+            FreeMobileError::ConfigError(format!(
+                "Template '{}' not found in {}",
+                name_or_path,
+                dir.display()
+            ))
+        };
+
+        let mut candidates = fs::read_dir(&dir)
+            .map_err(|_| not_found())?
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|candidate| {
+                candidate.is_file()
+                    && (candidate.file_name() == Some(path.as_os_str())
+                        || candidate.file_stem() == Some(path.as_os_str()))
+            })
+            .collect::<Vec<_>>();
+        // An exact file name wins over a file stem, then the first name in order
+        candidates.sort_by_key(|candidate| {
+            (
+                candidate.file_name() != Some(path.as_os_str()),
+                candidate.clone(),
+            )
+        });
+
+        candidates.into_iter().next().ok_or_else(not_found)
+    }
+
+    /// Names of the variables used by the template, in order of appearance
+    pub fn variables(&self) -> Vec<&str> {
+        let mut names = Vec::new();
+        for segment in &self.segments {
+            if let Segment::Variable(name) = segment
+                && !names.contains(&name.as_str())
+            {
+                names.push(name.as_str());
+            }
+        }
+        names
+    }
+
+    /// Fill the placeholders, failing with the list of missing variables
+    pub fn render(&self, variables: &Variables) -> Result<String, FreeMobileError> {
+        let missing = self
+            .variables()
+            .into_iter()
+            .filter(|name| variables.get(name).is_none())
+            .collect::<Vec<_>>();
+        if !missing.is_empty() {
+            return Err(FreeMobileError::ConfigError(format!(
+                "Missing template {}: {}, set with --var KEY=VALUE",
+                if missing.len() == 1 {
+                    "variable"
+                } else {
+                    "variables"
+                },
+                missing.join(", ")
+            )));
+        }
+
+        let mut output = String::new();
+        for segment in &self.segments {
+            match segment {
+                Segment::Literal(text) => output.push_str(text),
+                Segment::Variable(name) => {
+                    output.push_str(&variables.get(name).unwrap_or_default())
+                }
+            }
+        }
+        Ok(output)
+    }
+}
+
+/// Default template directory, e.g. `~/.config/send-sms/templates`
+pub fn default_dir() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join(CONFIG_DIR_NAME).join(TEMPLATE_DIR_NAME))
+}
+
+fn is_variable_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+/// Values of template variables, falling back to environment variables
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Variables {
+    values: HashMap<String, String>,
+}
+
+impl Variables {
+    /// Built-in variables: `hostname`, `date`, `time`, `user`, and `exit_code` if given
+    pub fn builtins(exit_code: Option<i32>) -> Self {
+        let now = chrono::Local::now();
+        let mut variables = Self::default();
+
+        variables.insert(
+            "hostname",
+            gethostname::gethostname().to_string_lossy().into_owned(),
+        );
+        variables.insert("date", now.format("%Y-%m-%d").to_string());
+        variables.insert("time", now.format("%H:%M:%S").to_string());
+        if let Some(user) = ["USER", "USERNAME"]
+            .into_iter()
+            .find_map(|name| env::var(name).ok())
+        {
+            variables.insert("user", user);
+        }
+        if let Some(exit_code) = exit_code {
+            variables.insert("exit_code", exit_code.to_string());
+        }
+
+        variables
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(name.into(), value.into());
+    }
+
+    /// Add the fields of a JSON object, nested fields being named `parent.child`
+    pub fn extend_json(&mut self, json: &str) -> Result<(), FreeMobileError> {
+        let value = serde_json::from_str::<Value>(json).map_err(|e| {
+            FreeMobileError::ConfigError(format!("Invalid JSON template variables: {}", e))
+        })?;
+        let Value::Object(object) = value else {
+            return Err(FreeMobileError::ConfigError(
+                "JSON template variables must be an object".to_string(),
+            ));
+        };
+
+        for (name, value) in object {
+            self.insert_json(name, value);
+        }
+        Ok(())
+    }
+
+    fn insert_json(&mut self, name: String, value: Value) {
+        match value {
+            Value::Object(object) => {
+                for (field, value) in object {
+                    self.insert_json(format!("{}.{}", name, field), value);
+                }
+            }
+            value => {
+                let text = json_to_text(&value);
+                self.values.insert(name, text);
+            }
+        }
+    }
+
+    /// Value of a variable, or of the environment variable of the same name
+    pub fn get(&self, name: &str) -> Option<String> {
+        self.values
+            .get(name)
+            .cloned()
+            .or_else(|| env::var(name).ok())
+    }
+}
+
+/// Text of a JSON value: strings unquoted, arrays as comma-separated lists
+fn json_to_text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(json_to_text)
+            .collect::<Vec<_>>()
+            .join(", "),
+        value => value.to_string(),
+    }
+}
+
+/// Parse a `--var KEY=VALUE` assignment
+pub fn parse_assignment(assignment: &str) -> Result<(String, String), FreeMobileError> {
+    match assignment.split_once('=') {
+        Some((name, value)) if is_variable_name(name.trim()) => {
+            Ok((name.trim().to_string(), value.to_string()))
+        }
+        _ => Err(FreeMobileError::ConfigError(format!(
+            "Invalid template variable '{}', expected KEY=VALUE",
+            assignment
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn variables(pairs: &[(&str, &str)]) -> Variables {
+        let mut variables = Variables::default();
+        for (name, value) in pairs {
+            variables.insert(*name, *value);
+        }
+        variables
+    }
+
+    #[test]
+    fn test_render() {
+        let template = Template::parse("{host}: {check} is {status}, {{ok}} }").unwrap();
+        assert_eq!(template.variables(), vec!["host", "check", "status"]);
+
+        let rendered = template
+            .render(&variables(&[
+                ("host", "web-01"),
+                ("check", "disk"),
+                ("status", "critical"),
+            ]))
+            .unwrap();
+        assert_eq!(rendered, "web-01: disk is critical, {ok} }");
+    }
+
+    #[test]
+    fn test_missing_variables() {
+        let template = Template::parse("{a} {b} {a} {c}").unwrap();
+        let error = template
+            .render(&variables(&[("b", "2")]))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Missing template variables: a, c"));
+
+        let error = Template::parse("{a}")
+            .unwrap()
+            .render(&Variables::default())
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Missing template variable: a,"));
+    }
+
+    #[test]
+    fn test_invalid_templates() {
+        assert!(Template::parse("Unclosed {host").is_err());
+        assert!(Template::parse("{not a name}").is_err());
+        assert!(Template::parse("{}").is_err());
+        assert!(Template::parse("{1st}").is_err());
+        assert!(Template::parse("{alert.labels.host}").is_ok());
+        assert!(
+            Template::parse("No placeholder")
+                .unwrap()
+                .variables()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_json_variables() {
+        let mut variables = Variables::default();
+        variables
+            .extend_json(
+                r#"{"status": "firing", "count": 3, "ok": false, "none": null,
+                    "alert": {"labels": {"host": "db-02"}}, "tags": ["prod", "eu"]}"#,
+            )
+            .unwrap();
+
+        let rendered = Template::parse("{status} {count} {ok} [{none}] {alert.labels.host} {tags}")
+            .unwrap()
+            .render(&variables)
+            .unwrap();
+        assert_eq!(rendered, "firing 3 false [] db-02 prod, eu");
+
+        assert!(variables.extend_json("[1, 2]").is_err());
+        assert!(variables.extend_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_builtins() {
+        let variables = Variables::builtins(Some(2));
+        assert_eq!(variables.get("exit_code").as_deref(), Some("2"));
+        assert!(variables.get("hostname").is_some());
+        assert_eq!(variables.get("date").unwrap().len(), "2025-01-31".len());
+        assert_eq!(variables.get("time").unwrap().len(), "12:00:00".len());
+
+        assert!(!Variables::builtins(None).values.contains_key("exit_code"));
+    }
+
+    #[test]
+    fn test_environment_fallback() {
+        let variables = variables(&[("PATH", "overridden")]);
+        assert_eq!(variables.get("PATH").as_deref(), Some("overridden"));
+        assert_eq!(Variables::default().get("PATH"), std::env::var("PATH").ok());
+        assert!(
+            Variables::default()
+                .get("SEND_SMS_UNDEFINED_VARIABLE")
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_parse_assignment() {
+        assert_eq!(
+            parse_assignment("status=a=b").unwrap(),
+            ("status".to_string(), "a=b".to_string())
+        );
+        assert_eq!(
+            parse_assignment("empty=").unwrap(),
+            ("empty".to_string(), String::new())
+        );
+        assert!(parse_assignment("no-value").is_err());
+        assert!(parse_assignment("=value").is_err());
+    }
+
+    #[test]
+    fn test_find() {
+        let tmp = TempDir::new().unwrap();
+        fs::write(tmp.path().join("disk-full.md"), "# {hostname}").unwrap();
+        fs::write(tmp.path().join("backup"), "{exit_code}").unwrap();
+        fs::write(tmp.path().join("backup.txt"), "{exit_code}").unwrap();
+
+        assert_eq!(
+            Template::find("disk-full", Some(tmp.path())).unwrap(),
+            tmp.path().join("disk-full.md")
+        );
+        assert_eq!(
+            Template::find("backup", Some(tmp.path())).unwrap(),
+            tmp.path().join("backup")
+        );
+
+        let path = tmp.path().join("backup.txt");
+        assert_eq!(Template::find(path.to_str().unwrap(), None).unwrap(), path);
+
+        let error = Template::find("unknown", Some(tmp.path()))
+            .unwrap_err()
+            .to_string();
+        assert!(error.contains("Template 'unknown' not found"));
+    }
+}
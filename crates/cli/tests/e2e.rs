@@ -0,0 +1,410 @@
+//! End-to-end tests running the `send-sms` binary against a local mock server
+
+use assert_cmd::Command;
+use freemobile_mock::{MockServer, ScriptedResponse};
+use predicates::prelude::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const USER: &str = "12345678";
+const PASS: &str = "secret";
+
+async fn mock_server(script: impl IntoIterator<Item = ScriptedResponse>) -> MockServer {
+    MockServer::builder()
+        .credentials(USER, PASS)
+        .script(script)
+        .start()
+        .await
+        .unwrap()
+}
+
+/// `send-sms` command isolated in its own temporary directory
+///
+/// The config and state directories, holding the rate limit and resume state
+/// files, are inside the temporary directory, so each test gets fresh ones.
+fn send_sms(server: &MockServer, tmp: &Path) -> Command {
+    let mut command = Command::cargo_bin("send-sms").unwrap();
+    command
+        .current_dir(tmp)
+        .env("TMPDIR", tmp)
+        .env("XDG_CONFIG_HOME", tmp.join("config"))
+        .env("XDG_RUNTIME_DIR", tmp.join("runtime"))
+        .env("XDG_CACHE_HOME", tmp.join("cache"))
+        .env_remove("FREEMOBILE_USER")
+        .env_remove("FREEMOBILE_PASS")
+        .env_remove("FREEMOBILE_CONFIG")
+        .env_remove("FREEMOBILE_PROFILE")
+        .args(["--api-url", &server.url(), "-u", USER]);
+    command
+}
+
+/// Path of the resume state file printed by a partially failed send
+fn resume_path(stderr: &[u8]) -> PathBuf {
+    let stderr = String::from_utf8_lossy(stderr);
+    let (_, path) = stderr
+        .split_once("resume with: send-sms --resume ")
+        .expect("No resume command");
+    PathBuf::from(path.lines().next().unwrap())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_send_message() {
+    let server = mock_server([]).await;
+    let tmp = TempDir::new().unwrap();
+
+    send_sms(&server, tmp.path())
+        .args(["-p", PASS, "-m", "Hello from CI 🚀"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("SMS sent"))
+        .stderr(predicate::str::contains("1 emoji replaced: 🚀"));
+
+    assert_eq!(server.messages(), vec!["Hello from CI []"]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_config_profiles() {
+    let server = mock_server([]).await;
+    let tmp = TempDir::new().unwrap();
+    let config_dir = tmp.path().join("config").join("send-sms");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(tmp.path().join("key"), format!("{}\n", PASS)).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        format!(
+            r#"
+[profiles.default]
+pass_file = "{}"
+emoji_policy = "shortcode"
+
+[profiles.wrong]
+pass = "wrong"
+"#,
+            tmp.path().join("key").display()
+        ),
+    )
+    .unwrap();
+
+    // The user comes from the command line, the key from the default profile
+    send_sms(&server, tmp.path())
+        .args(["-m", "🔥 disk full"])
+        .assert()
+        .success();
+
+    send_sms(&server, tmp.path())
+        .args(["--profile", "wrong", "-m", "Hello"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Access denied"));
+
+    // Env vars win over the profile
+    send_sms(&server, tmp.path())
+        .env("FREEMOBILE_PASS", PASS)
+        .args(["--profile", "wrong", "-m", "Hello"])
+        .assert()
+        .success();
+
+    assert_eq!(server.messages(), vec![":fire: disk full", "Hello"]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_invalid_credentials() {
+    let server = mock_server([]).await;
+    let tmp = TempDir::new().unwrap();
+
+    send_sms(&server, tmp.path())
+        .args(["-p", "wrong", "-m", "Hello"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Access denied"));
+
+    assert!(server.messages().is_empty());
+    assert_eq!(server.received()[0].status, 403);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_partial_send_and_resume() {
+    let server = mock_server([ScriptedResponse::status(200), ScriptedResponse::status(403)]).await;
+    let tmp = TempDir::new().unwrap();
+    let message = "lorem ipsum ".repeat(100);
+
+    let output = send_sms(&server, tmp.path())
+        .args(["-p", PASS, "-m", &message])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("resume with: send-sms --resume"))
+        .get_output()
+        .clone();
+
+    // Saved in the private state directory, not in the temporary directory
+    let state_file = resume_path(&output.stderr);
+    assert!(state_file.is_file());
+    assert!(fs::read_dir(tmp.path()).unwrap().all(|entry| {
+        !entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .starts_with("send-sms-resume-")
+    }));
+    assert_eq!(server.messages().len(), 1);
+
+    send_sms(&server, tmp.path())
+        .args(["-p", PASS, "--resume"])
+        .arg(&state_file)
+        .assert()
+        .success();
+
+    let messages = server.messages();
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0].starts_with("[1/2] "));
+    assert!(messages[1].starts_with("[2/2] "));
+    assert!(!state_file.exists());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_dry_run_sends_nothing() {
+    let server = mock_server([]).await;
+    let tmp = TempDir::new().unwrap();
+
+    send_sms(&server, tmp.path())
+        .args(["--dry-run", "--json", "-m", "Hello 🚀"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("\"sanitized\": \"Hello []\""));
+
+    assert!(server.received().is_empty());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_max_parts() {
+    let server = mock_server([]).await;
+    let tmp = TempDir::new().unwrap();
+    let message = "lorem ipsum ".repeat(300);
+
+    send_sms(&server, tmp.path())
+        .args(["-p", PASS, "--max-parts", "2", "-m", &message])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("more than the maximum of 2"));
+    assert!(server.received().is_empty());
+
+    send_sms(&server, tmp.path())
+        .args(["-p", PASS, "--max-parts", "2", "--overflow", "keep-last"])
+        .args(["-m", &message])
+        .assert()
+        .success();
+
+    let messages = server.messages();
+    assert_eq!(messages.len(), 2);
+    assert!(messages[0].starts_with("[1/2] "));
+    assert!(messages[1].ends_with("lorem ipsum"));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_emoji_policy() {
+    let server = mock_server([]).await;
+    let tmp = TempDir::new().unwrap();
+
+    send_sms(&server, tmp.path())
+        .args([
+            "-p",
+            PASS,
+            "--emoji-policy",
+            "closest",
+            "-m",
+            "🔥 disk full",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("1 emoji replaced: 🔥"));
+
+    send_sms(&server, tmp.path())
+        .args([
+            "-p",
+            PASS,
+            "--emoji-policy",
+            "shortcode",
+            "-m",
+            "🔥 disk full",
+        ])
+        .assert()
+        .success();
+
+    assert_eq!(server.messages(), vec!["⚠️ disk full", ":fire: disk full"]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_emoji_table() {
+    let server = mock_server([]).await;
+    let tmp = TempDir::new().unwrap();
+    let table = tmp.path().join("emojis.toml");
+    fs::write(
+        &table,
+        "supported = [\"🫠\"]\n[substitutions]\n\"🚀\" = \"(rocket)\"\n",
+    )
+    .unwrap();
+
+    send_sms(&server, tmp.path())
+        .args(["-p", PASS, "--emoji-table"])
+        .arg(&table)
+        .args(["-m", "🫠 🚀 😀"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("2 emojis replaced: 🚀 😀"));
+
+    fs::write(&table, "supported = \"🫠\"").unwrap();
+    send_sms(&server, tmp.path())
+        .args(["-p", PASS, "--emoji-table"])
+        .arg(&table)
+        .args(["-m", "🫠"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("emojis.toml"));
+
+    assert_eq!(server.messages(), vec!["🫠 (rocket) []"]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_tool_output_cleaned_up() {
+    let server = mock_server([]).await;
+    let tmp = TempDir::new().unwrap();
+
+    send_sms(&server, tmp.path())
+        .args(["-p", PASS])
+        .write_stdin("\x1B[32mPASSED\x1B[0m 12 tests\x07\n")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Cleaned up 2 ANSI escape sequences, 1 control character",
+        ));
+
+    send_sms(&server, tmp.path())
+        .args(["-p", PASS, "--control-chars", "escape", "-m", "ding\x07"])
+        .assert()
+        .success();
+
+    assert_eq!(server.messages(), vec!["PASSED 12 tests", "ding\\x07"]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_gsm7() {
+    let server = mock_server([]).await;
+    let tmp = TempDir::new().unwrap();
+
+    send_sms(&server, tmp.path())
+        .args([
+            "-p",
+            PASS,
+            "--gsm7",
+            "-m",
+            "Disque plein… l’équipe arrive — Hélène, fenêtre",
+        ])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains(
+            "Cleaned up 4 transliterated characters",
+        ));
+
+    assert_eq!(
+        server.messages(),
+        vec!["Disque plein... l'équipe arrive - Hélène, fenetre"]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_markdown_and_html_input() {
+    let server = mock_server([]).await;
+    let tmp = TempDir::new().unwrap();
+    fs::write(
+        tmp.path().join("NOTES.md"),
+        "# v1.2 **released**\n\n* [Changelog](https://example.com/v1.2)\n* Fixes &amp; docs\n",
+    )
+    .unwrap();
+
+    send_sms(&server, tmp.path())
+        .args(["-p", PASS, "-f", "NOTES.md"])
+        .assert()
+        .success();
+
+    send_sms(&server, tmp.path())
+        .args(["-p", PASS, "--format", "html"])
+        .write_stdin("<p>Disk <b>full</b> on&nbsp;web-01</p><ul><li>Clean /tmp</li></ul>")
+        .assert()
+        .success();
+
+    assert_eq!(
+        server.messages(),
+        vec![
+            "v1.2 released\n\n- Changelog (https://example.com/v1.2)\n- Fixes & docs",
+            "Disk full on\u{A0}web-01\n\n- Clean /tmp",
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_template() {
+    let server = mock_server([]).await;
+    let tmp = TempDir::new().unwrap();
+    let templates = tmp.path().join("templates");
+    fs::create_dir(&templates).unwrap();
+    fs::write(
+        templates.join("alert.md"),
+        "**{alert.host}** is {status} (code {exit_code}) {{ack}}\n",
+    )
+    .unwrap();
+
+    send_sms(&server, tmp.path())
+        .env("FREEMOBILE_TEMPLATE_DIR", &templates)
+        .args(["-p", PASS, "--template", "alert", "--exit-code", "2"])
+        .args(["--var", "status=down"])
+        .write_stdin(r#"{"alert": {"host": "db-02"}, "status": "firing"}"#)
+        .assert()
+        .success();
+
+    send_sms(&server, tmp.path())
+        .env("FREEMOBILE_TEMPLATE_DIR", &templates)
+        .args(["-p", PASS, "--template", "alert"])
+        .write_stdin("{}")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "Missing template variables: alert.host, status, exit_code",
+        ));
+
+    assert_eq!(server.messages(), vec!["db-02 is down (code 2) {ack}"]);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_probe_emojis() {
+    let server = mock_server([]).await;
+    let tmp = TempDir::new().unwrap();
+    fs::write(tmp.path().join("candidates.txt"), "🫠 🪿\n🚀 🫠\n").unwrap();
+
+    send_sms(&server, tmp.path())
+        .args(["-p", PASS, "probe-emojis", "send", "--candidates"])
+        .arg("candidates.txt")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("3 emojis numbered in 1 message,"));
+    assert_eq!(server.messages(), vec!["Emoji probe 1/1 1:🫠 2:🪿 3:🚀"]);
+
+    send_sms(&server, tmp.path())
+        .args([
+            "probe-emojis",
+            "report",
+            "--failed",
+            "2",
+            "-o",
+            "allow.toml",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("2 of 3 emojis rendered"));
+
+    send_sms(&server, tmp.path())
+        .args(["-p", PASS, "--emoji-table", "allow.toml", "-m", "🫠 🪿 🚀"])
+        .assert()
+        .success();
+    assert_eq!(server.messages()[1], "🫠 [] 🚀");
+}
@@ -0,0 +1,50 @@
+//! Chunking throughput on multi-megabyte messages
+//!
+//! Run with `cargo bench -p freemobile-api`. The throughput should stay the same
+//! whatever the size of the message, as chunking is linear.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use freemobile_api::{ChunkIter, MessageChunker, SplitStrategy};
+use std::hint::black_box;
+
+const SIZES_MB: [usize; 3] = [1, 2, 4];
+
+fn message(size_mb: usize) -> String {
+    let paragraph = "Lorem ipsum dolor sit amet, consectetur adipiscing elit. 🌟 Sed do eiusmod \
+                     tempor incididunt ut labore et dolore magna aliqua.\n\n";
+    paragraph.repeat(size_mb * 1024 * 1024 / paragraph.len())
+}
+
+fn bench_chunk(c: &mut Criterion) {
+    let mut group = c.benchmark_group("chunk");
+    group.sample_size(10);
+
+    for size_mb in SIZES_MB {
+        let message = message(size_mb);
+        group.throughput(Throughput::Bytes(message.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("chunk", size_mb),
+            &message,
+            |b, message| b.iter(|| MessageChunker::chunk(black_box(message))),
+        );
+        group.bench_with_input(BenchmarkId::new("iter", size_mb), &message, |b, message| {
+            b.iter(|| ChunkIter::new(black_box(message), 990).count())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("iter_paragraph", size_mb),
+            &message,
+            |b, message| {
+                b.iter(|| {
+                    ChunkIter::new(black_box(message), 990)
+                        .split(SplitStrategy::Paragraph, 200)
+                        .count()
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_chunk);
+criterion_main!(benches);
@@ -1,7 +1,387 @@
-use crate::constants::{
-    MAX_MESSAGE_LENGTH, PREFIX_RESERVE_LENGTH, word_boundary::MIN_BOUNDARY_RATIO,
-};
-use unicode_segmentation::UnicodeSegmentation;
+use crate::constants::overflow::{OMISSION_MARKER, TRUNCATION_MARKER};
+use crate::constants::word_boundary::{MIN_BOUNDARY_RATIO, SPLIT_TOLERANCE};
+use crate::constants::{DEFAULT_CHUNK_TEMPLATE, MAX_MESSAGE_LENGTH};
+use crate::error::FreeMobileError;
+use std::iter::{FusedIterator, Peekable};
+use std::ops::Range;
+use unicode_segmentation::{USentenceBoundIndices, UnicodeSegmentation};
+
+/// Position of the part marker in a formatted chunk
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MarkerPlacement {
+    /// Before the text, e.g. `[1/2] text`
+    #[default]
+    Prefix,
+    /// After the text, e.g. `text [1/2]`
+    Suffix,
+}
+
+/// Layout of the chunks of a multi-part message
+///
+/// Each chunk is made of the optional header, the part marker and the text,
+/// separated by spaces. The marker is only added when a message needs several
+/// chunks, while the header is repeated on every chunk.
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::{ChunkFormat, MarkerPlacement};
+///
+/// let format = ChunkFormat {
+///     template: "({i}/{n})".to_string(),
+///     placement: MarkerPlacement::Suffix,
+///     header: Some("web-01".to_string()),
+/// };
+/// assert_eq!(format.apply("Disk full", 1, 2), "web-01 Disk full (1/2)");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkFormat {
+    /// Part marker, `{i}` is replaced by the part number and `{n}` by the number of parts
+    pub template: String,
+    pub placement: MarkerPlacement,
+    /// Text repeated at the start of every chunk, e.g. a hostname
+    pub header: Option<String>,
+}
+
+impl Default for ChunkFormat {
+    fn default() -> Self {
+        Self {
+            template: DEFAULT_CHUNK_TEMPLATE.to_string(),
+            placement: MarkerPlacement::default(),
+            header: None,
+        }
+    }
+}
+
+impl ChunkFormat {
+    /// Part marker of the `index`-th chunk (starting at 1) out of `count`
+    pub fn marker(&self, index: usize, count: usize) -> String {
+        self.template
+            .replace("{i}", &index.to_string())
+            .replace("{n}", &count.to_string())
+    }
+
+    /// Format the `index`-th chunk (starting at 1) of a message split into `count` parts
+    pub fn apply(&self, text: &str, index: usize, count: usize) -> String {
+        let header = self.header.as_deref().filter(|header| !header.is_empty());
+        let marker = (count > 1).then(|| self.marker(index, count));
+
+        let parts: Vec<&str> = match self.placement {
+            MarkerPlacement::Prefix => [header, marker.as_deref(), Some(text)],
+            MarkerPlacement::Suffix => [header, Some(text), marker.as_deref()],
+        }
+        .into_iter()
+        .flatten()
+        .collect();
+
+        parts.join(" ")
+    }
+
+    /// Number of graphemes added to each chunk of a message split into `count` parts
+    ///
+    /// Part numbers never get wider than `count`, so this is an upper bound for
+    /// every chunk of the message.
+    pub fn reserved_length(&self, count: usize) -> usize {
+        self.apply("", count, count).graphemes(true).count()
+    }
+}
+
+/// What to do with a message needing more chunks than allowed
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Reject the message with `FreeMobileError::TooManyChunks`
+    #[default]
+    Refuse,
+    /// Keep the beginning of the message, ending with `TRUNCATION_MARKER`
+    Truncate,
+    /// Keep the beginning and the end, with `OMISSION_MARKER` in the middle
+    HeadTail,
+    /// Keep only the last chunks
+    KeepLast,
+}
+
+crate::named_enum!(Overflow, "overflow strategy", {
+    Refuse => "refuse",
+    Truncate => "truncate",
+    HeadTail => "head-tail",
+    KeepLast => "keep-last",
+});
+
+/// Where long messages are preferably split
+///
+/// Each strategy falls back to the next kind of boundary when none is found
+/// within the split tolerance, and ultimately to whitespace.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SplitStrategy {
+    /// At the last whitespace fitting in the chunk
+    #[default]
+    Whitespace,
+    /// At Unicode sentence boundaries, then at whitespace
+    Sentence,
+    /// At blank lines between paragraphs, then at sentence boundaries, then at whitespace
+    Paragraph,
+    /// At line breaks, keeping log lines whole, then at whitespace
+    Line,
+}
+
+crate::named_enum!(SplitStrategy, "split strategy", {
+    Whitespace => "whitespace",
+    Sentence => "sentence",
+    Paragraph => "paragraph",
+    Line => "line",
+});
+
+impl SplitStrategy {
+    /// Boundaries tried before whitespace, in order of preference
+    fn preferences(self) -> &'static [Boundary] {
+        match self {
+            Self::Whitespace => &[],
+            Self::Sentence => &[Boundary::Sentence],
+            Self::Paragraph => &[Boundary::Paragraph, Boundary::Sentence],
+            Self::Line => &[Boundary::Line],
+        }
+    }
+}
+
+/// Position a chunk can preferably end at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Boundary {
+    Paragraph,
+    Line,
+    Sentence,
+}
+
+/// Last position of a boundary in the chunk being built
+#[derive(Debug, Clone, Copy)]
+struct BoundaryPos {
+    bytes: usize,
+    graphemes: usize,
+}
+
+/// Chunking settings of a message
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::{ChunkOptions, MessageChunker, Overflow};
+///
+/// let options = ChunkOptions {
+///     max_chunks: Some(3),
+///     overflow: Overflow::HeadTail,
+///     ..ChunkOptions::default()
+/// };
+/// let chunks = MessageChunker::chunk_with_options(&"lorem ipsum ".repeat(1000), &options)?;
+/// assert_eq!(chunks.len(), 3);
+/// # Ok::<(), freemobile_api::FreeMobileError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkOptions {
+    pub format: ChunkFormat,
+    /// Maximum number of chunks of a message (at least 1), unlimited when `None`
+    pub max_chunks: Option<usize>,
+    /// What to do with messages needing more than `max_chunks` chunks
+    pub overflow: Overflow,
+    /// Where chunks are preferably split
+    pub split: SplitStrategy,
+    /// Maximum number of graphemes left unused at the end of a chunk to split
+    /// it at a boundary preferred by `split` (defaults to `SPLIT_TOLERANCE`)
+    pub split_tolerance: usize,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        Self {
+            format: ChunkFormat::default(),
+            max_chunks: None,
+            overflow: Overflow::default(),
+            split: SplitStrategy::default(),
+            split_tolerance: SPLIT_TOLERANCE,
+        }
+    }
+}
+
+/// Chunk of a message yielded by [`ChunkIter`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chunk<'a> {
+    /// Text of the chunk, without surrounding whitespace
+    pub text: &'a str,
+    /// Position of `text` in the message, in bytes
+    pub byte_range: Range<usize>,
+    /// Number of graphemes of `text`
+    pub grapheme_count: usize,
+}
+
+/// Lazy iterator over the chunks of a message
+///
+/// Chunks are found in a single pass over the message and borrow its text.
+/// Each one holds at most `chunk_limit` graphemes and is not formatted: the
+/// limit must leave room for the part markers, e.g.
+/// `MAX_MESSAGE_LENGTH - format.reserved_length(count)`.
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::ChunkIter;
+///
+/// let chunks: Vec<_> = ChunkIter::new("one two three four", 9).collect();
+/// assert_eq!(chunks[0].text, "one two");
+/// assert_eq!(chunks[1].byte_range, 8..13);
+/// assert_eq!(chunks[2].grapheme_count, 4);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ChunkIter<'a> {
+    message: &'a str,
+    /// Byte offset where the next chunk starts
+    position: usize,
+    chunk_limit: usize,
+    split: SplitStrategy,
+    tolerance: usize,
+    /// Sentence boundaries, only consumed by strategies preferring them
+    sentences: Peekable<USentenceBoundIndices<'a>>,
+}
+
+impl<'a> ChunkIter<'a> {
+    /// Iterate over chunks of at most `chunk_limit` graphemes (at least 1)
+    pub fn new(message: &'a str, chunk_limit: usize) -> Self {
+        Self {
+            message,
+            position: 0,
+            chunk_limit: chunk_limit.max(1),
+            split: SplitStrategy::default(),
+            tolerance: SPLIT_TOLERANCE,
+            sentences: message.split_sentence_bound_indices().peekable(),
+        }
+    }
+
+    /// End chunks at the boundaries of `split` found in their last `tolerance`
+    /// graphemes, or else at their last whitespace
+    pub fn split(mut self, split: SplitStrategy, tolerance: usize) -> Self {
+        self.split = split;
+        self.tolerance = tolerance;
+        self
+    }
+
+    /// Chunk made of the text between `start` and `end`, without surrounding whitespace
+    fn chunk(&self, start: usize, end: usize) -> Chunk<'a> {
+        let raw = &self.message[start..end];
+        let text = raw.trim();
+        let offset = start + raw.len() - raw.trim_start().len();
+
+        Chunk {
+            text,
+            byte_range: offset..offset + text.len(),
+            grapheme_count: text.graphemes(true).count(),
+        }
+    }
+}
+
+impl<'a> Iterator for ChunkIter<'a> {
+    type Item = Chunk<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Skip whitespace before the chunk, ending on whitespace-only text
+        let remaining = self.message[self.position..].trim_start();
+        let start = self.message.len() - remaining.len();
+        self.position = start;
+        if remaining.is_empty() {
+            return None;
+        }
+        let preferences = self.split.preferences();
+
+        // Sentence boundaries past the start of the chunk, looked ahead without
+        // consuming them as the chunk may end before
+        let tracks_sentences = preferences.contains(&Boundary::Sentence);
+        if tracks_sentences {
+            while self
+                .sentences
+                .next_if(|&(bound, _)| bound <= start)
+                .is_some()
+            {}
+        }
+        let mut sentences = self.sentences.clone();
+
+        // Build the chunk grapheme by grapheme, tracking the last good boundaries
+        let mut graphemes = remaining.graphemes(true);
+        let mut last_word_boundary_pos = 0;
+        let mut last_paragraph = None;
+        let mut last_line = None;
+        let mut last_sentence = None;
+        let mut after_newline = false;
+        let mut byte_pos = 0;
+        let mut taken = 0;
+
+        while taken < self.chunk_limit {
+            let Some(grapheme) = graphemes.next() else {
+                break;
+            };
+
+            // Add the grapheme
+            byte_pos += grapheme.len();
+            taken += 1;
+            let position = Some(BoundaryPos {
+                bytes: byte_pos,
+                graphemes: taken,
+            });
+
+            // Update word boundary position if this is whitespace
+            if grapheme.chars().any(|c| c.is_whitespace()) {
+                last_word_boundary_pos = byte_pos;
+            }
+
+            // A line break right after another one ends a paragraph
+            if grapheme.contains('\n') {
+                if after_newline {
+                    last_paragraph = position;
+                }
+                last_line = position;
+                after_newline = true;
+            } else if !grapheme.chars().all(char::is_whitespace) {
+                after_newline = false;
+            }
+
+            if tracks_sentences {
+                let offset = start + byte_pos;
+                while sentences.next_if(|&(bound, _)| bound < offset).is_some() {}
+                if sentences.peek().is_some_and(|&(bound, _)| bound == offset) {
+                    last_sentence = position;
+                }
+            }
+        }
+
+        // If remaining text is short enough, take it all
+        if graphemes.next().is_none() {
+            self.position = self.message.len();
+            return Some(self.chunk(start, self.message.len()));
+        }
+
+        // Use the first preferred boundary close enough to the end of the chunk
+        let preferred = preferences.iter().find_map(|boundary| {
+            let last = match boundary {
+                Boundary::Paragraph => last_paragraph,
+                Boundary::Line => last_line,
+                Boundary::Sentence => last_sentence,
+            };
+            last.filter(|pos| taken - pos.graphemes <= self.tolerance)
+                .map(|pos| pos.bytes)
+        });
+
+        // Otherwise, if we found a word boundary and it's not too close to the beginning, use it
+        let split_pos = match preferred {
+            Some(pos) => pos,
+            None if last_word_boundary_pos > byte_pos / MIN_BOUNDARY_RATIO => {
+                last_word_boundary_pos
+            }
+            // Safety fallback: take at least one grapheme
+            None => byte_pos,
+        };
+
+        let end = start + split_pos;
+        self.position = end;
+        Some(self.chunk(start, end))
+    }
+}
+
+impl FusedIterator for ChunkIter<'_> {}
 
 /// Message chunker for handling FreeMobile's length limits
 pub struct MessageChunker;
@@ -11,93 +391,229 @@ impl MessageChunker {
     /// Uses Unicode grapheme-aware splitting to handle complex characters correctly
     /// Reserves space for chunk prefixes like "[1/2] " when multiple chunks are needed
     pub fn chunk(message: &str) -> Vec<String> {
+        Self::chunk_with(message, &ChunkFormat::default())
+    }
+
+    /// Splits a message into chunks that fit FreeMobile's 999 character limit once
+    /// formatted with `format`
+    ///
+    /// The space reserved for the header and part marker is computed from the
+    /// format and the final number of chunks, so every formatted chunk fits the
+    /// limit whatever the number of parts, as long as the format leaves room for
+    /// at least one grapheme of text.
+    pub fn chunk_with(message: &str, format: &ChunkFormat) -> Vec<String> {
+        Self::chunk_split(message, format, SplitStrategy::default(), SPLIT_TOLERANCE)
+    }
+
+    /// Splits a message for `format`, preferably at the boundaries of `split`
+    fn chunk_split(
+        message: &str,
+        format: &ChunkFormat,
+        split: SplitStrategy,
+        tolerance: usize,
+    ) -> Vec<String> {
         // Early return for empty or whitespace-only messages
         if message.trim().is_empty() {
             return vec![];
         }
 
-        // For single chunk, use full length limit
-        if message.graphemes(true).count() <= MAX_MESSAGE_LENGTH {
+        // For single chunk, use full length limit minus the header
+        let length = message.graphemes(true).count();
+        if length + format.reserved_length(1) <= MAX_MESSAGE_LENGTH {
             return vec![message.to_string()];
         }
 
-        // For multiple chunks, reserve space for prefixes like "[1/2] "
-        let effective_chunk_limit = MAX_MESSAGE_LENGTH - PREFIX_RESERVE_LENGTH;
-        let mut chunks = Vec::new();
-        let mut current_pos = 0;
+        // For multiple chunks, reserve space for the header and the widest part marker.
+        // Splitting on word boundaries can produce more chunks than the estimate,
+        // widening the markers (e.g. "[100/100] "): split again with the actual
+        // count until it stops growing.
+        let mut count = 2;
+        loop {
+            count = count.max(length.div_ceil(Self::chunk_limit(format, count)));
 
-        while current_pos < message.len() {
-            let remaining = &message[current_pos..];
+            let chunks: Vec<String> = ChunkIter::new(message, Self::chunk_limit(format, count))
+                .split(split, tolerance)
+                .map(|chunk| chunk.text.to_string())
+                .collect();
+            if chunks.len() <= count {
+                return chunks;
+            }
+            count = chunks.len();
+        }
+    }
 
-            // If remaining text is short enough, take it all
-            if remaining.graphemes(true).count() <= effective_chunk_limit {
-                chunks.push(remaining.trim().to_string());
-                break;
+    /// Splits a message into chunks formatted with `options.format`, keeping at
+    /// most `options.max_chunks` chunks
+    ///
+    /// # Errors
+    ///
+    /// Returns `FreeMobileError::TooManyChunks` if the message needs more chunks
+    /// than allowed and the overflow strategy is `Overflow::Refuse`.
+    pub fn chunk_with_options(
+        message: &str,
+        options: &ChunkOptions,
+    ) -> Result<Vec<String>, FreeMobileError> {
+        let mut chunks = Self::chunk_split(
+            message,
+            &options.format,
+            options.split,
+            options.split_tolerance,
+        );
+        let Some(max) = options.max_chunks.map(|max| max.max(1)) else {
+            return Ok(chunks);
+        };
+        let count = chunks.len();
+        if count <= max {
+            return Ok(chunks);
+        }
+
+        // Fewer chunks never need wider markers, so the kept chunks still fit
+        let room = Self::chunk_limit(&options.format, max);
+        match options.overflow {
+            Overflow::Refuse => return Err(FreeMobileError::TooManyChunks { count, max }),
+            Overflow::KeepLast => {
+                chunks.drain(..count - max);
+            }
+            Overflow::Truncate => {
+                chunks.truncate(max);
+                let last = chunks.pop().unwrap_or_default();
+                let last = Self::append_marker(&last, "", TRUNCATION_MARKER, room);
+                chunks.push(last);
+            }
+            Overflow::HeadTail => {
+                chunks = Self::head_tail(message, options, max, room);
             }
+        }
 
-            // Build the chunk character by character, tracking the last good word boundary
-            let mut chunk_text = String::new();
-            let mut last_word_boundary_pos = 0;
-            let mut byte_pos = 0;
+        Ok(chunks)
+    }
 
-            for (chars_count, grapheme) in remaining.graphemes(true).enumerate() {
-                // Check if adding this grapheme would exceed the limit
-                if chars_count >= effective_chunk_limit {
-                    break;
-                }
+    /// Keep the beginning and the end of a message in `max` chunks of at most
+    /// `room` graphemes, the last chunk of the beginning ending with
+    /// `OMISSION_MARKER`
+    ///
+    /// The end is split backwards from the end of the message at whitespace, so
+    /// that every chunk of the end is filled.
+    fn head_tail(message: &str, options: &ChunkOptions, max: usize, room: usize) -> Vec<String> {
+        let mut head: Vec<Chunk> = ChunkIter::new(message, room)
+            .split(options.split, options.split_tolerance)
+            .take(max.div_ceil(2))
+            .collect();
+        let head_end = head.last().map_or(0, |chunk| chunk.byte_range.end);
+        let tail = Self::tail_ranges(message, head_end, room, max - head.len());
+        let tail_start = tail
+            .first()
+            .map_or(message.trim_end().len(), |range| range.start);
+        let tail = tail.into_iter().map(|range| message[range].to_string());
 
-                // Add the grapheme
-                chunk_text.push_str(grapheme);
-                byte_pos += grapheme.len();
+        // Nothing left between both ends, e.g. for messages split in small chunks
+        if message[head_end..tail_start].trim().is_empty() {
+            return head
+                .iter()
+                .map(|chunk| chunk.text.to_string())
+                .chain(tail)
+                .collect();
+        }
 
-                // Update word boundary position if this is whitespace
-                if grapheme.chars().any(|c| c.is_whitespace()) {
-                    last_word_boundary_pos = byte_pos;
-                }
-            }
+        // Size the marker for the worst case, as cutting the last head chunk to
+        // make room for it adds to the omitted characters
+        let omitted_from = |end: usize| message[end..tail_start].graphemes(true).count();
+        let last = head.pop().expect("Head of an overflowing message");
+        let widest =
+            OMISSION_MARKER.replace("{n}", &omitted_from(last.byte_range.start).to_string());
+        let kept = Self::fit_before_marker(last.text, " ", &widest, room);
+        let marker = OMISSION_MARKER.replace(
+            "{n}",
+            &omitted_from(last.byte_range.start + kept.len()).to_string(),
+        );
+        let last = if kept.is_empty() {
+            marker
+        } else {
+            format!("{} {}", kept, marker)
+        };
 
-            // If we found a word boundary and it's not too close to the beginning, use it
-            let split_pos = if last_word_boundary_pos > byte_pos / MIN_BOUNDARY_RATIO {
-                last_word_boundary_pos
-            } else {
-                byte_pos
-            };
+        head.iter()
+            .map(|chunk| chunk.text.to_string())
+            .chain([last])
+            .chain(tail)
+            .collect()
+    }
+
+    /// Byte ranges of up to `parts` chunks of at most `room` graphemes, split
+    /// backwards from the end of the message down to `after`
+    fn tail_ranges(message: &str, after: usize, room: usize, parts: usize) -> Vec<Range<usize>> {
+        let mut ranges = Vec::with_capacity(parts);
+        let mut end = after + message[after..].trim_end().len();
 
-            if split_pos > 0 {
-                let chunk_text = &remaining[..split_pos];
-                chunks.push(chunk_text.trim().to_string());
-                current_pos += split_pos;
-
-                // Skip any whitespace at the start of the next chunk
-                while current_pos < message.len() {
-                    let ch = message[current_pos..].chars().next().unwrap();
-                    if ch.is_whitespace() {
-                        current_pos += ch.len_utf8();
-                    } else {
-                        break;
-                    }
+        while ranges.len() < parts && end > after {
+            let start = message[after..end]
+                .grapheme_indices(true)
+                .rev()
+                .take(room)
+                .last()
+                .map_or(end, |(index, _)| after + index);
+
+            // Start after a whitespace unless it is too close to the end
+            let window = &message[start..end];
+            let start = match window.find(char::is_whitespace) {
+                Some(_) if start == after || message[..start].ends_with(char::is_whitespace) => {
+                    start
                 }
-            } else {
-                // Safety fallback: take at least one character
-                let next_char = remaining.chars().next().unwrap();
-                chunks.push(next_char.to_string());
-                current_pos += next_char.len_utf8();
-            }
+                Some(position) if window.len() - position > window.len() / MIN_BOUNDARY_RATIO => {
+                    start + position
+                }
+                _ => start,
+            };
+
+            let text = message[start..end].trim_start();
+            let start = end - text.len();
+            ranges.push(start..end);
+            end = after + message[after..start].trim_end().len();
         }
 
-        chunks
+        ranges.reverse();
+        ranges
+    }
+
+    /// Beginning of `text` leaving room for a separator and a marker within
+    /// `room` graphemes
+    fn fit_before_marker<'a>(text: &'a str, separator: &str, marker: &str, room: usize) -> &'a str {
+        let marker_length = separator.graphemes(true).count() + marker.graphemes(true).count();
+        let end = text
+            .grapheme_indices(true)
+            .nth(room.saturating_sub(marker_length))
+            .map_or(text.len(), |(index, _)| index);
+        text[..end].trim_end()
+    }
+
+    /// Append a marker to a chunk, cutting its end to keep it within `room` graphemes
+    fn append_marker(chunk: &str, separator: &str, marker: &str, room: usize) -> String {
+        let text = Self::fit_before_marker(chunk, separator, marker, room);
+        if text.is_empty() {
+            marker.to_string()
+        } else {
+            format!("{}{}{}", text, separator, marker)
+        }
+    }
+
+    /// Maximum number of graphemes of the text of each chunk, for `count` chunks
+    fn chunk_limit(format: &ChunkFormat, count: usize) -> usize {
+        MAX_MESSAGE_LENGTH
+            .saturating_sub(format.reserved_length(count))
+            .max(1)
     }
 
     /// Formats chunks with index prefixes for multi-part messages
     pub fn format_chunks(chunks: &[String]) -> Vec<String> {
-        if chunks.len() <= 1 {
-            return chunks.to_vec();
-        }
+        Self::format_chunks_with(chunks, &ChunkFormat::default())
+    }
 
+    /// Formats chunks with the header and part markers of `format`
+    pub fn format_chunks_with(chunks: &[String], format: &ChunkFormat) -> Vec<String> {
         chunks
             .iter()
             .enumerate()
-            .map(|(index, chunk)| format!("[{}/{}] {}", index + 1, chunks.len(), chunk))
+            .map(|(index, chunk)| format.apply(chunk, index + 1, chunks.len()))
             .collect()
     }
 }
@@ -105,6 +621,116 @@ impl MessageChunker {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+
+    /// Check that every formatted chunk fits the API limit and that no text was lost
+    fn assert_chunks_fit(message: &str, format: &ChunkFormat) {
+        let chunks = MessageChunker::chunk_with(message, format);
+        let formatted = MessageChunker::format_chunks_with(&chunks, format);
+
+        for chunk in &formatted {
+            let length = chunk.graphemes(true).count();
+            assert!(
+                length <= MAX_MESSAGE_LENGTH,
+                "Chunk of {} graphemes exceeds the API limit ({} parts)",
+                length,
+                formatted.len()
+            );
+        }
+
+        let visible = |text: &str| {
+            text.chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>()
+        };
+        assert_eq!(visible(&chunks.concat()), visible(message));
+    }
+
+    fn any_format() -> impl Strategy<Value = ChunkFormat> {
+        (
+            prop::sample::select(vec![
+                "[{i}/{n}]",
+                "({i}/{n})",
+                "{i}/{n}:",
+                "…cont.",
+                "part {i} of {n}",
+            ]),
+            prop::bool::ANY,
+            prop::option::of("\\PC{0,40}"),
+        )
+            .prop_map(|(template, suffix, header)| ChunkFormat {
+                template: template.to_string(),
+                placement: if suffix {
+                    MarkerPlacement::Suffix
+                } else {
+                    MarkerPlacement::Prefix
+                },
+                header,
+            })
+    }
+
+    fn assert_chunk_offsets(message: &str, chunk_limit: usize, split: SplitStrategy) {
+        let mut end = 0;
+        for chunk in ChunkIter::new(message, chunk_limit).split(split, SPLIT_TOLERANCE) {
+            assert_eq!(&message[chunk.byte_range.clone()], chunk.text);
+            assert_eq!(chunk.grapheme_count, chunk.text.graphemes(true).count());
+            assert!(chunk.grapheme_count <= chunk_limit);
+            assert!(chunk.byte_range.start >= end);
+            end = chunk.byte_range.end;
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn prop_chunk_iter_offsets(
+            message in "(\\PC{1,12}[ .\n]{0,3}){0,300}",
+            chunk_limit in 1usize..200,
+            split in prop::sample::select(SplitStrategy::NAMES.to_vec()),
+        ) {
+            assert_chunk_offsets(&message, chunk_limit, split.parse().unwrap());
+        }
+
+        #[test]
+        fn prop_arbitrary_unicode_fits(
+            chars in prop::collection::vec(any::<char>(), 0..4000),
+            format in any_format(),
+        ) {
+            assert_chunks_fit(&String::from_iter(chars), &format);
+        }
+
+        #[test]
+        fn prop_tiny_fragments_fit(
+            fragments in prop::collection::vec("\\PC{1,3}", 0..1500),
+            separators in prop::collection::vec(prop::sample::select(vec![" ", "\n", "  ", "\t"]), 1..8),
+            format in any_format(),
+        ) {
+            let message: String = fragments
+                .iter()
+                .zip(separators.iter().cycle())
+                .map(|(fragment, separator)| format!("{}{}", fragment, separator))
+                .collect();
+            assert_chunks_fit(&message, &format);
+        }
+
+        #[test]
+        fn prop_head_tail_fits(
+            message in "(\\PC{1,12}[ .\n]{0,3}){0,600}",
+            max_chunks in 1usize..5,
+        ) {
+            let options = ChunkOptions {
+                max_chunks: Some(max_chunks),
+                overflow: Overflow::HeadTail,
+                ..ChunkOptions::default()
+            };
+            let chunks = MessageChunker::chunk_with_options(&message, &options).unwrap();
+            prop_assert!(chunks.len() <= max_chunks);
+            for chunk in MessageChunker::format_chunks(&chunks) {
+                prop_assert!(chunk.graphemes(true).count() <= MAX_MESSAGE_LENGTH);
+            }
+        }
+    }
 
     #[test]
     fn test_short_message_no_chunking() {
@@ -121,7 +747,8 @@ mod tests {
         assert!(chunks.len() > 1);
 
         // Test that raw chunks fit within effective limit
-        let effective_limit = MAX_MESSAGE_LENGTH - PREFIX_RESERVE_LENGTH;
+        let effective_limit =
+            MAX_MESSAGE_LENGTH - ChunkFormat::default().reserved_length(chunks.len());
         assert!(
             chunks
                 .iter()
@@ -169,6 +796,389 @@ mod tests {
         assert_eq!(formatted[1], "[2/2] Second chunk");
     }
 
+    #[test]
+    fn test_custom_templates() {
+        let chunks = vec!["First".to_string(), "Second".to_string()];
+
+        let parenthesized = ChunkFormat {
+            template: "({i}/{n})".to_string(),
+            ..ChunkFormat::default()
+        };
+        assert_eq!(
+            MessageChunker::format_chunks_with(&chunks, &parenthesized),
+            vec!["(1/2) First", "(2/2) Second"]
+        );
+
+        let colon = ChunkFormat {
+            template: "{i}/{n}:".to_string(),
+            ..ChunkFormat::default()
+        };
+        assert_eq!(colon.apply("text", 3, 12), "3/12: text");
+
+        let continued = ChunkFormat {
+            template: "…cont.".to_string(),
+            placement: MarkerPlacement::Suffix,
+            header: None,
+        };
+        assert_eq!(continued.apply("text", 1, 2), "text …cont.");
+    }
+
+    #[test]
+    fn test_header_on_every_chunk() {
+        let format = ChunkFormat {
+            header: Some("web-01".to_string()),
+            ..ChunkFormat::default()
+        };
+
+        assert_eq!(
+            MessageChunker::format_chunks_with(&["Single".to_string()], &format),
+            vec!["web-01 Single"]
+        );
+        assert_eq!(
+            MessageChunker::format_chunks_with(&["A".to_string(), "B".to_string()], &format),
+            vec!["web-01 [1/2] A", "web-01 [2/2] B"]
+        );
+    }
+
+    #[test]
+    fn test_reserved_length() {
+        let format = ChunkFormat::default();
+        assert_eq!(format.reserved_length(1), 0);
+        assert_eq!(format.reserved_length(2), "[2/2] ".len());
+        assert_eq!(format.reserved_length(120), "[120/120] ".len());
+
+        let suffix = ChunkFormat {
+            template: "…cont.".to_string(),
+            placement: MarkerPlacement::Suffix,
+            header: Some("héllo".to_string()),
+        };
+        assert_eq!(suffix.reserved_length(2), "héllo  …cont.".chars().count());
+    }
+
+    #[test]
+    fn test_chunk_with_format_fits_limit() {
+        let format = ChunkFormat {
+            template: "part {i} of {n} ->".to_string(),
+            placement: MarkerPlacement::Suffix,
+            header: Some("host.example.com".to_string()),
+        };
+        let message = "lorem ipsum ".repeat(300);
+
+        let chunks = MessageChunker::chunk_with(&message, &format);
+        let formatted = MessageChunker::format_chunks_with(&chunks, &format);
+
+        assert!(formatted.len() > 1);
+        assert!(
+            formatted
+                .iter()
+                .all(|chunk| chunk.graphemes(true).count() <= MAX_MESSAGE_LENGTH)
+        );
+        assert!(formatted[0].starts_with("host.example.com lorem"));
+        assert!(formatted[0].ends_with(&format!("part 1 of {} ->", formatted.len())));
+    }
+
+    #[test]
+    fn test_header_counts_for_single_chunk() {
+        let format = ChunkFormat {
+            header: Some("web-01".to_string()),
+            ..ChunkFormat::default()
+        };
+        let message = "a".repeat(MAX_MESSAGE_LENGTH);
+
+        assert_eq!(MessageChunker::chunk(&message).len(), 1);
+        assert_eq!(MessageChunker::chunk_with(&message, &format).len(), 2);
+    }
+
+    #[test]
+    fn test_more_than_99_parts() {
+        // Long words split into a full chunk and a short one need far more parts
+        // than the length estimate, widening the markers to "[100/100] "
+        let message = format!("{} ", "x".repeat(1_500)).repeat(50);
+        let chunks = MessageChunker::chunk(&message);
+        let formatted = MessageChunker::format_chunks(&chunks);
+
+        assert!(chunks.len() > 99);
+        assert!(formatted[0].starts_with(&format!("[1/{}] ", chunks.len())));
+        assert_chunks_fit(&message, &ChunkFormat::default());
+    }
+
+    fn limited(max_chunks: usize, overflow: Overflow) -> ChunkOptions {
+        ChunkOptions {
+            max_chunks: Some(max_chunks),
+            overflow,
+            ..ChunkOptions::default()
+        }
+    }
+
+    /// Numbered words, to tell which part of the message was kept
+    fn numbered_words(count: usize) -> String {
+        (0..count).map(|i| format!("w{} ", i)).collect()
+    }
+
+    #[test]
+    fn test_max_chunks_within_limit() {
+        let message = numbered_words(300);
+        let options = limited(5, Overflow::Refuse);
+
+        assert_eq!(
+            MessageChunker::chunk_with_options(&message, &options).unwrap(),
+            MessageChunker::chunk(&message)
+        );
+        assert_eq!(
+            MessageChunker::chunk_with_options(&message, &ChunkOptions::default()).unwrap(),
+            MessageChunker::chunk(&message)
+        );
+    }
+
+    #[test]
+    fn test_overflow_refuse() {
+        let message = numbered_words(1000);
+        let result = MessageChunker::chunk_with_options(&message, &limited(2, Overflow::Refuse));
+
+        assert!(matches!(
+            result,
+            Err(FreeMobileError::TooManyChunks { count, max: 2 }) if count > 2
+        ));
+    }
+
+    #[test]
+    fn test_overflow_truncate() {
+        let message = numbered_words(1000);
+        let chunks =
+            MessageChunker::chunk_with_options(&message, &limited(2, Overflow::Truncate)).unwrap();
+
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[0].starts_with("w0 w1 "));
+        assert!(chunks[1].ends_with(TRUNCATION_MARKER));
+        assert!(!chunks[1].contains("w999"));
+        assert!(
+            MessageChunker::format_chunks(&chunks)
+                .iter()
+                .all(|chunk| chunk.graphemes(true).count() <= MAX_MESSAGE_LENGTH)
+        );
+    }
+
+    #[test]
+    fn test_overflow_head_tail() {
+        let message = numbered_words(1000);
+        let chunks =
+            MessageChunker::chunk_with_options(&message, &limited(3, Overflow::HeadTail)).unwrap();
+        let formatted = MessageChunker::format_chunks(&chunks);
+
+        assert_eq!(chunks.len(), 3);
+        assert!(chunks[0].starts_with("w0 "));
+        assert!(chunks[2].ends_with("w999"));
+
+        // The omitted text goes from the kept text of the second chunk to the tail
+        let room = MessageChunker::chunk_limit(&ChunkFormat::default(), 3);
+        let second = ChunkIter::new(&message, room).nth(1).unwrap();
+        let marker_start = chunks[1].find("[… ").unwrap();
+        let kept = chunks[1][..marker_start].trim_end();
+        assert!(second.text.starts_with(kept));
+        let kept_end = second.byte_range.start + kept.len();
+        let tail_start = message.trim_end().len() - chunks[2].len();
+        let omitted = message[kept_end..tail_start].graphemes(true).count();
+        assert_eq!(
+            &chunks[1][marker_start..],
+            format!("[… {} chars omitted …]", omitted)
+        );
+        assert!(
+            formatted
+                .iter()
+                .all(|chunk| chunk.graphemes(true).count() <= MAX_MESSAGE_LENGTH)
+        );
+    }
+
+    #[test]
+    fn test_overflow_head_tail_fills_tail() {
+        let message = "word ".repeat(600);
+        let chunks =
+            MessageChunker::chunk_with_options(&message, &limited(2, Overflow::HeadTail)).unwrap();
+        let room = MessageChunker::chunk_limit(&ChunkFormat::default(), 2);
+
+        assert_eq!(chunks.len(), 2);
+        let tail = chunks[1].graphemes(true).count();
+        assert!(
+            tail <= room && tail > room - 5,
+            "Tail of {} graphemes",
+            tail
+        );
+        assert!(message.trim_end().ends_with(&chunks[1]));
+        assert!(chunks[1].starts_with("word"));
+    }
+
+    #[test]
+    fn test_overflow_head_tail_single_chunk() {
+        let chunks =
+            MessageChunker::chunk_with_options(&"a".repeat(3000), &limited(1, Overflow::HeadTail))
+                .unwrap();
+
+        assert_eq!(chunks.len(), 1);
+        assert!(chunks[0].graphemes(true).count() <= MAX_MESSAGE_LENGTH);
+        let kept = chunks[0].find(' ').unwrap();
+        assert_eq!(
+            &chunks[0][kept..],
+            format!(" [… {} chars omitted …]", 3000 - kept)
+        );
+    }
+
+    #[test]
+    fn test_overflow_keep_last() {
+        let message = numbered_words(1000);
+        let chunks =
+            MessageChunker::chunk_with_options(&message, &limited(2, Overflow::KeepLast)).unwrap();
+        let formatted = MessageChunker::format_chunks(&chunks);
+
+        assert_eq!(chunks.len(), 2);
+        assert!(chunks[1].ends_with("w999"));
+        assert!(formatted[0].starts_with("[1/2] "));
+    }
+
+    #[test]
+    fn test_overflow_names() {
+        for name in Overflow::NAMES {
+            assert_eq!(name.parse::<Overflow>().unwrap().to_string(), name);
+        }
+        assert!("drop".parse::<Overflow>().is_err());
+    }
+
+    fn split_by(split: SplitStrategy) -> ChunkOptions {
+        ChunkOptions {
+            split,
+            ..ChunkOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_split_sentence() {
+        let message: String = (0..60)
+            .map(|i| format!("Sentence number {} keeps going for a little while. ", i))
+            .collect();
+
+        let whitespace = MessageChunker::chunk(&message);
+        assert!(whitespace.iter().any(|chunk| !chunk.ends_with('.')));
+
+        let chunks =
+            MessageChunker::chunk_with_options(&message, &split_by(SplitStrategy::Sentence))
+                .unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.ends_with("while.")));
+    }
+
+    #[test]
+    fn test_split_paragraph() {
+        let paragraph = "Some words in a sentence. ".repeat(11) + "END.";
+        let message = vec![paragraph; 12].join("\n\n");
+
+        let chunks =
+            MessageChunker::chunk_with_options(&message, &split_by(SplitStrategy::Paragraph))
+                .unwrap();
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|chunk| chunk.ends_with("END.")));
+        assert_chunks_fit(&message, &ChunkFormat::default());
+    }
+
+    #[test]
+    fn test_split_line_keeps_log_lines_whole() {
+        let lines: Vec<String> = (0..60)
+            .map(|i| {
+                format!(
+                    "2026-10-16 12:00:{:02} INFO request {} handled in 12 ms",
+                    i, i
+                )
+            })
+            .collect();
+        let message = lines.join("\n");
+
+        let chunks =
+            MessageChunker::chunk_with_options(&message, &split_by(SplitStrategy::Line)).unwrap();
+        assert!(chunks.len() > 1);
+        for chunk in &chunks {
+            assert!(chunk.lines().all(|line| lines.iter().any(|l| l == line)));
+        }
+    }
+
+    #[test]
+    fn test_split_tolerance() {
+        let message = "Intro.\n\n".to_string() + &"word ".repeat(400);
+
+        // The paragraph break is too far from the end of the first chunk
+        let chunks =
+            MessageChunker::chunk_with_options(&message, &split_by(SplitStrategy::Paragraph))
+                .unwrap();
+        assert!(chunks[0].starts_with("Intro.\n\nword"));
+
+        let tolerant = ChunkOptions {
+            split_tolerance: MAX_MESSAGE_LENGTH,
+            ..split_by(SplitStrategy::Paragraph)
+        };
+        let chunks = MessageChunker::chunk_with_options(&message, &tolerant).unwrap();
+        assert_eq!(chunks[0], "Intro.");
+    }
+
+    #[test]
+    fn test_chunk_iter() {
+        let message = "  Hello world. 🌟🌟 Second line\nlast  ";
+        let chunks: Vec<Chunk> = ChunkIter::new(message, 14).collect();
+
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.text).collect::<Vec<_>>(),
+            vec!["Hello world.", "🌟🌟 Second", "line\nlast"]
+        );
+        assert_eq!(chunks[1].grapheme_count, 9);
+        for chunk in &chunks {
+            assert_eq!(&message[chunk.byte_range.clone()], chunk.text);
+        }
+        assert_chunk_offsets(
+            &"lorem ipsum dolor ".repeat(200),
+            50,
+            SplitStrategy::Sentence,
+        );
+    }
+
+    #[test]
+    fn test_chunk_iter_skips_whitespace() {
+        assert_eq!(ChunkIter::new(" \n\t  ", 3).count(), 0);
+        assert_eq!(ChunkIter::new("", 3).count(), 0);
+
+        let message = format!("{}Hello world", " ".repeat(20));
+        let chunks: Vec<Chunk> = ChunkIter::new(&message, 5).collect();
+        assert_eq!(
+            chunks.iter().map(|chunk| chunk.text).collect::<Vec<_>>(),
+            vec!["Hello", "world"]
+        );
+        assert_eq!(chunks[0].byte_range, 20..25);
+    }
+
+    #[test]
+    fn test_chunk_iter_is_lazy() {
+        let message = "word ".repeat(1_000_000);
+        let mut chunks = ChunkIter::new(&message, 990);
+
+        let first = chunks.next().unwrap();
+        assert_eq!(first.byte_range, 0..989);
+        assert_eq!(chunks.next().unwrap().byte_range.start, 990);
+    }
+
+    #[test]
+    fn test_chunk_matches_iter() {
+        let message = "Some words in a sentence. ".repeat(200);
+        let chunks = MessageChunker::chunk(&message);
+        let limit = MAX_MESSAGE_LENGTH - ChunkFormat::default().reserved_length(chunks.len());
+        let iterated: Vec<&str> = ChunkIter::new(&message, limit)
+            .map(|chunk| chunk.text)
+            .collect();
+        assert_eq!(chunks, iterated);
+    }
+
+    #[test]
+    fn test_split_names() {
+        for name in SplitStrategy::NAMES {
+            assert_eq!(name.parse::<SplitStrategy>().unwrap().to_string(), name);
+        }
+        assert!("word".parse::<SplitStrategy>().is_err());
+    }
+
     #[test]
     fn test_empty_chunk_handling() {
         let message = "   \n\n   ";
@@ -210,7 +1220,7 @@ mod tests {
     #[test]
     fn test_word_boundary_respect() {
         // Test that words are not broken in the middle
-        let words = vec![
+        let words = [
             "hello",
             "world",
             "testing",
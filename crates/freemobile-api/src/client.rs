@@ -1,16 +1,31 @@
-use crate::chunker::MessageChunker;
-use crate::constants::{API_URL, CHUNK_DELAY_MS, REQUEST_TIMEOUT_SECS, USER_AGENT};
+use crate::chunker::{ChunkFormat, ChunkOptions, Overflow, SplitStrategy};
+use crate::constants::{
+    API_URL, CHUNK_DELAY_MS, MAX_MESSAGE_LENGTH, REQUEST_TIMEOUT_SECS, USER_AGENT,
+};
+use crate::emoji_table::EmojiTable;
 use crate::error::FreeMobileError;
-use crate::sanitizer::MessageSanitizer;
-use reqwest::Client;
-use serde::{Deserialize, Serialize};
-use std::time::Duration;
+use crate::plan::SendPlan;
+use crate::rate_limit::RateLimiter;
+use crate::report::{ChunkOutcome, ChunkReport, SendReport};
+use crate::retry::RetryPolicy;
+use crate::sanitizer::{MessageSanitizer, SanitizeOptions, SanitizePolicy};
+use crate::secret::{ApiKey, REDACTED};
+use crate::transport::{ReqwestTransport, SmsRequest, SmsTransport, TransportResponse};
+use reqwest::{Client, Proxy, Url};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+use std::time::{Duration, Instant};
 
 /// FreeMobile API credentials
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The API key never appears in `Debug` or `Display` output, and serializing
+/// credentials leaves it out unless [`Credentials::with_exposed_key`] is used.
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Credentials {
     pub user: String,
-    pub pass: String,
+    #[serde(skip_serializing)]
+    pub pass: ApiKey,
 }
 
 impl Credentials {
@@ -19,7 +34,7 @@ impl Credentials {
     /// # Arguments
     ///
     /// * `user` - FreeMobile user ID (8 digits)
-    /// * `pass` - FreeMobile API key
+    /// * `pass` - FreeMobile API key, as a `String` or an [`ApiKey`]
     ///
     /// # Example
     ///
@@ -31,20 +46,67 @@ impl Credentials {
     ///     "your-api-key".to_string()
     /// );
     /// ```
-    pub fn new(user: String, pass: String) -> Self {
-        Self { user, pass }
+    pub fn new(user: String, pass: impl Into<ApiKey>) -> Self {
+        Self {
+            user,
+            pass: pass.into(),
+        }
     }
 
     /// Check if credentials are valid (non-empty)
     pub fn is_valid(&self) -> bool {
-        !self.user.trim().is_empty() && !self.pass.trim().is_empty()
+        !self.user.trim().is_empty() && !self.pass.expose().trim().is_empty()
+    }
+
+    /// Serializable view of the credentials including the API key
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use freemobile_api::Credentials;
+    ///
+    /// let credentials = Credentials::new("12345678".to_string(), "your-api-key".to_string());
+    /// let json = serde_json::to_string(&credentials.with_exposed_key()).unwrap();
+    /// assert_eq!(json, r#"{"user":"12345678","pass":"your-api-key"}"#);
+    /// ```
+    pub fn with_exposed_key(&self) -> ExposedCredentials<'_> {
+        ExposedCredentials(self)
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("user", &self.user)
+            .field("pass", &format_args!("{}", REDACTED))
+            .finish()
+    }
+}
+
+impl fmt::Display for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.user, REDACTED)
+    }
+}
+
+/// Credentials serialized with their API key, see [`Credentials::with_exposed_key`]
+#[derive(Debug, Clone, Copy)]
+pub struct ExposedCredentials<'a>(&'a Credentials);
+
+impl Serialize for ExposedCredentials<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Credentials", 2)?;
+        state.serialize_field("user", &self.0.user)?;
+        state.serialize_field("pass", self.0.pass.expose())?;
+        state.end()
     }
 }
 
 /// FreeMobile SMS API client
 ///
 /// This client handles all communication with the FreeMobile API, including
-/// message sanitization, chunking, and proper error handling.
+/// message sanitization, chunking, and proper error handling. Delivery of each
+/// chunk is delegated to an [`SmsTransport`], [`ReqwestTransport`] by default.
 ///
 /// # Example
 ///
@@ -65,13 +127,246 @@ impl Credentials {
 /// }
 /// ```
 #[derive(Debug)]
-pub struct FreeMobileClient {
-    client: Client,
+pub struct FreeMobileClient<T: SmsTransport = ReqwestTransport> {
+    transport: T,
     credentials: Credentials,
+    chunk_delay: Duration,
+    chunk_options: ChunkOptions,
+    sanitize_options: SanitizeOptions,
+    emoji_table: EmojiTable,
+    retry_policy: RetryPolicy,
+    rate_limiter: RateLimiter,
+}
+
+/// Builder for [`FreeMobileClient`]
+///
+/// Every setting defaults to the values of the `constants` module, so
+/// `FreeMobileClientBuilder::new(credentials).build()` is equivalent to
+/// `FreeMobileClient::new(credentials)`.
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::{Credentials, FreeMobileClientBuilder};
+/// use std::time::Duration;
+///
+/// let credentials = Credentials::new("12345678".to_string(), "your-api-key".to_string());
+/// let client = FreeMobileClientBuilder::new(credentials)
+///     .base_url("http://127.0.0.1:8080/sendmsg")
+///     .timeout(Duration::from_secs(5))
+///     .chunk_delay(Duration::ZERO)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct FreeMobileClientBuilder {
+    credentials: Credentials,
+    base_url: String,
+    timeout: Duration,
+    user_agent: String,
+    chunk_delay: Duration,
+    chunk_options: ChunkOptions,
+    sanitize_options: SanitizeOptions,
+    emoji_table: EmojiTable,
+    retry_policy: RetryPolicy,
+    rate_limiter: RateLimiter,
+    proxy: Option<Proxy>,
+    http_client: Option<Client>,
+}
+
+impl FreeMobileClientBuilder {
+    /// Create a builder initialized with the default settings
+    pub fn new(credentials: Credentials) -> Self {
+        Self {
+            credentials,
+            base_url: API_URL.to_string(),
+            timeout: Duration::from_secs(REQUEST_TIMEOUT_SECS),
+            user_agent: USER_AGENT.to_string(),
+            chunk_delay: Duration::from_millis(CHUNK_DELAY_MS),
+            chunk_options: ChunkOptions::default(),
+            sanitize_options: SanitizeOptions::default(),
+            emoji_table: EmojiTable::builtin(),
+            retry_policy: RetryPolicy::default(),
+            rate_limiter: RateLimiter::default(),
+            proxy: None,
+            http_client: None,
+        }
+    }
+
+    /// Set the endpoint messages are sent to (defaults to `API_URL`)
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Set the HTTP request timeout (defaults to `REQUEST_TIMEOUT_SECS`)
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Set the user agent sent with each request (defaults to `USER_AGENT`)
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = user_agent.into();
+        self
+    }
+
+    /// Set the delay between consecutive chunks (defaults to `CHUNK_DELAY_MS`)
+    pub fn chunk_delay(mut self, chunk_delay: Duration) -> Self {
+        self.chunk_delay = chunk_delay;
+        self
+    }
+
+    /// Set the layout of multi-part messages (defaults to `[i/n] ` prefixes)
+    pub fn chunk_format(mut self, chunk_format: ChunkFormat) -> Self {
+        self.chunk_options.format = chunk_format;
+        self
+    }
+
+    /// Limit the number of chunks of a message (unlimited by default)
+    ///
+    /// Messages needing more chunks are handled according to `overflow`.
+    pub fn max_chunks(mut self, max_chunks: usize, overflow: Overflow) -> Self {
+        self.chunk_options.max_chunks = Some(max_chunks);
+        self.chunk_options.overflow = overflow;
+        self
+    }
+
+    /// Set where long messages are preferably split (defaults to whitespace)
+    pub fn split(mut self, split: SplitStrategy) -> Self {
+        self.chunk_options.split = split;
+        self
+    }
+
+    /// Set what unsupported emojis are replaced with (defaults to `EMOJI_PLACEHOLDER`)
+    pub fn sanitize_policy(mut self, sanitize_policy: SanitizePolicy) -> Self {
+        self.sanitize_options.policy = sanitize_policy;
+        self
+    }
+
+    /// Set the sanitizer settings, including its emoji policy
+    /// (defaults to `SanitizeOptions::default()`, every cleanup pass enabled)
+    pub fn sanitize_options(mut self, sanitize_options: SanitizeOptions) -> Self {
+        self.sanitize_options = sanitize_options;
+        self
+    }
+
+    /// Set the supported emojis and substitutions used by the sanitizer
+    /// (defaults to `EmojiTable::builtin()`)
+    pub fn emoji_table(mut self, emoji_table: EmojiTable) -> Self {
+        self.emoji_table = emoji_table;
+        self
+    }
+
+    /// Set the retry policy applied to each chunk (defaults to `RetryPolicy::default()`)
+    ///
+    /// Use `RetryPolicy::none()` to send each chunk only once.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    /// Set the rate limiter throttling every request (defaults to `RateLimiter::default()`)
+    ///
+    /// The limiter applies to all the sends of the client, including concurrent
+    /// ones and retries. Pass a clone of the same limiter to several clients to
+    /// share a budget between them.
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Route all requests through the given proxy
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Use a pre-built HTTP client
+    ///
+    /// The timeout, user agent and proxy settings of this builder are ignored
+    /// when a client is provided, as they are part of the client itself.
+    pub fn http_client(mut self, client: Client) -> Self {
+        self.http_client = Some(client);
+        self
+    }
+
+    /// Build the client
+    ///
+    /// # Errors
+    ///
+    /// * `FreeMobileError::ConfigError` - If the user ID or API key is empty, or
+    ///   the base URL cannot be parsed
+    /// * `FreeMobileError::HttpError` - If HTTP client creation fails
+    pub fn build(mut self) -> Result<FreeMobileClient, FreeMobileError> {
+        let base_url = Url::parse(&self.base_url).map_err(|e| {
+            FreeMobileError::ConfigError(format!("Invalid API URL '{}': {}", self.base_url, e))
+        })?;
+
+        let client = match self.http_client.take() {
+            Some(client) => client,
+            None => {
+                let mut builder = Client::builder()
+                    .timeout(self.timeout)
+                    .user_agent(&self.user_agent);
+                if let Some(proxy) = self.proxy.take() {
+                    builder = builder.proxy(proxy);
+                }
+                builder.build().map_err(FreeMobileError::HttpError)?
+            }
+        };
+
+        let transport = ReqwestTransport::new(client, base_url);
+        self.build_with_transport(transport)
+    }
+
+    /// Build the client on top of a custom transport
+    ///
+    /// The base URL, timeout, user agent, proxy and HTTP client settings of this
+    /// builder are ignored, as they only apply to the default [`ReqwestTransport`].
+    ///
+    /// # Errors
+    ///
+    /// * `FreeMobileError::ConfigError` - If the user ID or API key is empty, the
+    ///   chunk format leaves no room for the message, or the maximum number of chunks is 0
+    pub fn build_with_transport<T: SmsTransport>(
+        self,
+        transport: T,
+    ) -> Result<FreeMobileClient<T>, FreeMobileError> {
+        if !self.credentials.is_valid() {
+            return Err(FreeMobileError::ConfigError(
+                "Credentials need a user ID and an API key".to_string(),
+            ));
+        }
+        if self.chunk_options.format.reserved_length(2) >= MAX_MESSAGE_LENGTH {
+            return Err(FreeMobileError::ConfigError(
+                "Chunk header and marker leave no room for the message".to_string(),
+            ));
+        }
+        if self.chunk_options.max_chunks == Some(0) {
+            return Err(FreeMobileError::ConfigError(
+                "Maximum number of chunks must be at least 1".to_string(),
+            ));
+        }
+
+        Ok(FreeMobileClient {
+            transport,
+            credentials: self.credentials,
+            chunk_delay: self.chunk_delay,
+            chunk_options: self.chunk_options,
+            sanitize_options: self.sanitize_options,
+            emoji_table: self.emoji_table,
+            retry_policy: self.retry_policy,
+            rate_limiter: self.rate_limiter,
+        })
+    }
 }
 
 impl FreeMobileClient {
-    /// Create a new FreeMobile client
+    /// Create a new FreeMobile client with the default settings
+    ///
+    /// Use [`FreeMobileClient::builder`] to customize the endpoint, timeouts
+    /// or HTTP client.
     ///
     /// # Arguments
     ///
@@ -79,23 +374,22 @@ impl FreeMobileClient {
     ///
     /// # Errors
     ///
-    /// Returns `FreeMobileError::InvalidCredentials` if credentials are invalid
+    /// Returns `FreeMobileError::ConfigError` if the user ID or API key is empty
     /// or `FreeMobileError::HttpError` if HTTP client creation fails.
     pub fn new(credentials: Credentials) -> Result<Self, FreeMobileError> {
-        if !credentials.is_valid() {
-            return Err(FreeMobileError::InvalidCredentials);
-        }
+        Self::builder(credentials).build()
+    }
 
-        let client = Client::builder()
-            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
-            .user_agent(USER_AGENT)
-            .build()
-            .map_err(FreeMobileError::HttpError)?;
+    /// Create a builder to configure a new client
+    pub fn builder(credentials: Credentials) -> FreeMobileClientBuilder {
+        FreeMobileClientBuilder::new(credentials)
+    }
+}
 
-        Ok(Self {
-            client,
-            credentials,
-        })
+impl<T: SmsTransport> FreeMobileClient<T> {
+    /// Transport used to deliver messages
+    pub fn transport(&self) -> &T {
+        &self.transport
     }
 
     /// Send an SMS message
@@ -104,17 +398,25 @@ impl FreeMobileClient {
     /// - Message sanitization (emoji replacement)
     /// - Message chunking for long texts
     /// - Rate limiting between chunks
+    /// - Retrying failed chunks according to the retry policy
     ///
     /// # Arguments
     ///
     /// * `message` - The message to send (will be sanitized automatically)
     ///
+    /// # Returns
+    ///
+    /// A [`SendReport`] listing every chunk sent, with its status, latency and attempts
+    ///
     /// # Errors
     ///
     /// * `FreeMobileError::EmptyMessage` - If message is empty after trimming
+    /// * `FreeMobileError::TooManyChunks` - If the message needs more chunks than allowed
     /// * `FreeMobileError::InvalidCredentials` - If API credentials are rejected  
     /// * `FreeMobileError::TooManyRequests` - If rate limit is exceeded
     /// * `FreeMobileError::HttpError` - For network-related errors
+    /// * `FreeMobileError::RetriesExhausted` - If a chunk still failed after retrying
+    /// * `FreeMobileError::PartialSend` - If a chunk failed after previous chunks were sent
     ///
     /// # Example
     ///
@@ -128,12 +430,12 @@ impl FreeMobileClient {
     /// # Ok(())
     /// # }
     /// ```
-    pub async fn send(&self, message: &str) -> Result<(), FreeMobileError> {
+    pub async fn send(&self, message: &str) -> Result<SendReport, FreeMobileError> {
         if message.trim().is_empty() {
             return Err(FreeMobileError::EmptyMessage);
         }
 
-        let sanitized_message = MessageSanitizer::sanitize(message);
+        let sanitized_message = self.sanitize_message(message);
         self.send_sanitized(&sanitized_message).await
     }
 
@@ -145,29 +447,110 @@ impl FreeMobileClient {
     /// # Arguments
     ///
     /// * `sanitized_message` - Pre-sanitized message content
-    pub async fn send_sanitized(&self, sanitized_message: &str) -> Result<(), FreeMobileError> {
+    ///
+    /// # Returns
+    ///
+    /// A [`SendReport`] listing every chunk sent
+    ///
+    /// # Errors
+    ///
+    /// When a chunk fails after at least one chunk was sent, the error is wrapped in
+    /// `FreeMobileError::PartialSend` with a report telling which chunks reached the API.
+    pub async fn send_sanitized(
+        &self,
+        sanitized_message: &str,
+    ) -> Result<SendReport, FreeMobileError> {
         if sanitized_message.trim().is_empty() {
             return Err(FreeMobileError::EmptyMessage);
         }
 
-        let chunks = MessageChunker::chunk(sanitized_message);
-        let formatted_chunks = MessageChunker::format_chunks(&chunks);
+        let plan = SendPlan::with_options(sanitized_message, &self.chunk_options)?;
+        self.send_plan(&plan).await
+    }
 
-        for (index, chunk) in formatted_chunks.iter().enumerate() {
-            self.send_chunk(chunk).await?;
+    /// Send all the chunks of a plan
+    ///
+    /// Equivalent to `resume(plan, 0)`.
+    pub async fn send_plan(&self, plan: &SendPlan) -> Result<SendReport, FreeMobileError> {
+        self.resume(plan, 0).await
+    }
+
+    /// Send the chunks of a plan starting at `from_index`
+    ///
+    /// Chunks keep their original `[n/N]` numbering, so a message interrupted by
+    /// a failure can be completed without sending duplicates. The returned report
+    /// only lists the chunks from `from_index` onwards, with their original index.
+    ///
+    /// # Example
+    ///
+    /// ```rust,no_run
+    /// # use freemobile_api::{FreeMobileClient, FreeMobileError, Credentials, SendPlan};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// # let credentials = Credentials::new("12345678".to_string(), "key".to_string());
+    /// # let client = FreeMobileClient::new(credentials)?;
+    /// # let long_message = "lorem ipsum ".repeat(200);
+    /// if let Err(FreeMobileError::PartialSend { report, .. }) = client.send(&long_message).await {
+    ///     let plan = SendPlan::from_report(&report);
+    ///     let from_index = report.first_unsent_index().unwrap_or(plan.len());
+    ///     client.resume(&plan, from_index).await?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// * `FreeMobileError::InvalidMessage` - If `from_index` is past the end of the plan
+    /// * `FreeMobileError::PartialSend` - If a chunk failed after previous chunks were sent
+    pub async fn resume(
+        &self,
+        plan: &SendPlan,
+        from_index: usize,
+    ) -> Result<SendReport, FreeMobileError> {
+        if from_index > plan.len() {
+            return Err(FreeMobileError::InvalidMessage(format!(
+                "Cannot resume from chunk {}: message has {} chunks",
+                from_index + 1,
+                plan.len()
+            )));
+        }
+
+        let mut report = SendReport::default();
+        for (index, chunk) in plan.chunks.iter().enumerate().skip(from_index) {
+            let (chunk_report, result) = self.send_chunk_with_retry(index, chunk).await;
+            report.chunks.push(chunk_report);
+
+            if let Err(error) = result {
+                if index == from_index {
+                    return Err(error);
+                }
+
+                report.chunks.extend(
+                    plan.chunks
+                        .iter()
+                        .enumerate()
+                        .skip(index + 1)
+                        .map(|(index, text)| ChunkReport::not_sent(index, text.clone())),
+                );
+                return Err(FreeMobileError::PartialSend {
+                    report: Box::new(report),
+                    source: Box::new(error),
+                });
+            }
 
             // Add delay between chunks to respect rate limits
-            if index < formatted_chunks.len() - 1 {
-                tokio::time::sleep(Duration::from_millis(CHUNK_DELAY_MS)).await;
+            if index < plan.len() - 1 {
+                tokio::time::sleep(self.chunk_delay).await;
             }
         }
 
-        Ok(())
+        Ok(report)
     }
 
     /// Sanitize a message without sending it
     ///
-    /// This method applies the same emoji sanitization that would be applied
+    /// This method applies the same sanitization that would be applied
     /// during sending, useful for previewing changes.
     ///
     /// # Arguments
@@ -176,34 +559,131 @@ impl FreeMobileClient {
     ///
     /// # Returns
     ///
-    /// The sanitized message with supported emojis preserved and unsupported ones replaced with []
+    /// The sanitized message with supported emojis preserved and unsupported ones
+    /// replaced according to the sanitize policy
     pub fn sanitize_message(&self, message: &str) -> String {
-        MessageSanitizer::sanitize(message)
+        MessageSanitizer::sanitize_with_options(message, &self.sanitize_options, &self.emoji_table)
+            .sanitized
     }
 
-    /// Send a single chunk (internal method)
-    async fn send_chunk(&self, message: &str) -> Result<(), FreeMobileError> {
-        let request = self.client.get(API_URL).query(&[
-            ("user", &self.credentials.user),
-            ("pass", &self.credentials.pass),
-            ("msg", &message.to_string()),
-        ]);
+    /// Build the plan of a message without sending it
+    ///
+    /// The plan contains the sanitized message, the formatted chunks exactly as
+    /// [`send`](Self::send) would deliver them and the emojis replaced by the
+    /// sanitizer. It can be sent later with [`send_plan`](Self::send_plan).
+    ///
+    /// # Errors
+    ///
+    /// * `FreeMobileError::EmptyMessage` - If message is empty after trimming
+    /// * `FreeMobileError::TooManyChunks` - If the message needs more chunks than allowed
+    pub fn plan(&self, message: &str) -> Result<SendPlan, FreeMobileError> {
+        if message.trim().is_empty() {
+            return Err(FreeMobileError::EmptyMessage);
+        }
+
+        let report = MessageSanitizer::sanitize_with_options(
+            message,
+            &self.sanitize_options,
+            &self.emoji_table,
+        );
+        SendPlan::from_sanitized(report, &self.chunk_options)
+    }
 
-        let response = request.send().await.map_err(FreeMobileError::HttpError)?;
+    /// Send a single chunk, retrying according to the retry policy (internal method)
+    ///
+    /// Returns the report of the chunk along with the result of its last attempt.
+    async fn send_chunk_with_retry(
+        &self,
+        index: usize,
+        message: &str,
+    ) -> (ChunkReport, Result<(), FreeMobileError>) {
+        let started = Instant::now();
+        let mut attempt = 1;
+        let mut last_response;
 
-        if !response.status().is_success() {
-            return Err(FreeMobileError::from_status_code(
-                response.status().as_u16(),
-            ));
-        }
+        let result = loop {
+            let error = match self.send_chunk(message).await {
+                Ok(response) if response.is_success() => {
+                    last_response = Some(response);
+                    break Ok(());
+                }
+                Ok(response) => {
+                    let error =
+                        FreeMobileError::from_response(response.status, response.body.clone());
+                    last_response = Some(response);
+                    error
+                }
+                Err(error) => {
+                    last_response = None;
+                    error
+                }
+            };
 
-        Ok(())
+            if self.retry_policy.should_retry(attempt, &error) {
+                tokio::time::sleep(self.retry_policy.delay_for(attempt, &error)).await;
+                attempt += 1;
+            } else if attempt > 1 {
+                break Err(FreeMobileError::RetriesExhausted {
+                    attempts: attempt,
+                    source: Box::new(error),
+                });
+            } else {
+                break Err(error);
+            }
+        };
+
+        let outcome = match &result {
+            Ok(()) => ChunkOutcome::Sent,
+            Err(error) => ChunkOutcome::Failed(error.to_string()),
+        };
+        let report = ChunkReport {
+            index,
+            text: message.to_string(),
+            status: last_response.as_ref().map(|response| response.status),
+            body: last_response
+                .filter(|response| !response.is_success())
+                .map(|response| response.body),
+            latency: started.elapsed(),
+            attempts: attempt,
+            outcome,
+        };
+
+        (report, result)
+    }
+
+    /// Send a single chunk once the rate limiter allows it (internal method)
+    async fn send_chunk(&self, message: &str) -> Result<TransportResponse, FreeMobileError> {
+        self.rate_limiter.acquire().await?;
+
+        let request = SmsRequest {
+            credentials: &self.credentials,
+            message,
+        };
+
+        self.transport.send(request).await
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::chunker::MarkerPlacement;
+    use crate::sanitizer::ControlChars;
+    use crate::testing::RecordingTransport;
+
+    fn recording_client(transport: &RecordingTransport) -> FreeMobileClient<RecordingTransport> {
+        let creds = Credentials::new("12345678".to_string(), "abcdef123".to_string());
+        FreeMobileClient::builder(creds)
+            .chunk_delay(Duration::ZERO)
+            .retry_policy(RetryPolicy {
+                base_delay: Duration::ZERO,
+                rate_limit_cooldown: Duration::ZERO,
+                ..RetryPolicy::default()
+            })
+            .rate_limiter(RateLimiter::unlimited())
+            .build_with_transport(transport.clone())
+            .unwrap()
+    }
 
     #[test]
     fn test_credentials_validation() {
@@ -214,6 +694,35 @@ mod tests {
         assert!(!invalid_creds.is_valid());
     }
 
+    #[test]
+    fn test_credentials_redacted() {
+        let creds = Credentials::new("12345678".to_string(), "super-secret".to_string());
+
+        let debug = format!("{:?}", creds);
+        assert_eq!(
+            debug,
+            r#"Credentials { user: "12345678", pass: [REDACTED] }"#
+        );
+        assert_eq!(creds.to_string(), "12345678:[REDACTED]");
+
+        let client = FreeMobileClient::new(creds).unwrap();
+        assert!(!format!("{:?}", client).contains("super-secret"));
+    }
+
+    #[test]
+    fn test_credentials_serialization_opt_in() {
+        let creds = Credentials::new("12345678".to_string(), "super-secret".to_string());
+
+        let json = serde_json::to_string(&creds).unwrap();
+        assert_eq!(json, r#"{"user":"12345678"}"#);
+
+        let json = serde_json::to_string(&creds.with_exposed_key()).unwrap();
+        assert_eq!(json, r#"{"user":"12345678","pass":"super-secret"}"#);
+
+        let parsed: Credentials = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.pass.expose(), "super-secret");
+    }
+
     #[test]
     fn test_client_creation() {
         let valid_creds = Credentials::new("12345678".to_string(), "abcdef123".to_string());
@@ -225,6 +734,516 @@ mod tests {
         assert!(client.is_err());
     }
 
+    #[test]
+    fn test_builder_defaults() {
+        let creds = Credentials::new("12345678".to_string(), "abcdef123".to_string());
+        let client = FreeMobileClient::builder(creds).build().unwrap();
+
+        assert_eq!(client.transport().url().as_str(), API_URL);
+        assert_eq!(client.chunk_delay, Duration::from_millis(CHUNK_DELAY_MS));
+    }
+
+    #[test]
+    fn test_builder_custom_settings() {
+        let creds = Credentials::new("12345678".to_string(), "abcdef123".to_string());
+        let client = FreeMobileClient::builder(creds)
+            .base_url("http://127.0.0.1:8080/sendmsg")
+            .timeout(Duration::from_secs(5))
+            .user_agent("custom-agent/1.0")
+            .chunk_delay(Duration::ZERO)
+            .proxy(Proxy::all("http://127.0.0.1:3128").unwrap())
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            client.transport().url().as_str(),
+            "http://127.0.0.1:8080/sendmsg"
+        );
+        assert_eq!(client.chunk_delay, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_builder_with_http_client() {
+        let creds = Credentials::new("12345678".to_string(), "abcdef123".to_string());
+        let client = FreeMobileClient::builder(creds)
+            .http_client(Client::new())
+            .build();
+        assert!(client.is_ok());
+    }
+
+    #[test]
+    fn test_builder_invalid_url() {
+        let creds = Credentials::new("12345678".to_string(), "abcdef123".to_string());
+        let result = FreeMobileClient::builder(creds)
+            .base_url("not a url")
+            .build();
+        assert!(matches!(result, Err(FreeMobileError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_builder_invalid_credentials() {
+        let creds = Credentials::new("".to_string(), "abcdef123".to_string());
+        let error = FreeMobileClient::builder(creds).build().unwrap_err();
+        assert!(matches!(error, FreeMobileError::ConfigError(_)));
+        assert_eq!(error.status(), None);
+        assert!(!error.is_auth_error());
+    }
+
+    #[test]
+    fn test_build_with_transport_invalid_credentials() {
+        let creds = Credentials::new("12345678".to_string(), " ".to_string());
+        let result =
+            FreeMobileClient::builder(creds).build_with_transport(RecordingTransport::new());
+        assert!(matches!(result, Err(FreeMobileError::ConfigError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_http_error_hides_api_key() {
+        // Nothing listens on the port of a dropped listener
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/sendmsg", listener.local_addr().unwrap());
+        drop(listener);
+
+        let creds = Credentials::new("12345678".to_string(), "secret-api-key".to_string());
+        let client = FreeMobileClient::builder(creds)
+            .base_url(url)
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap();
+
+        let error = client.send("Hello").await.unwrap_err();
+        let message = format!("{} {:?}", error, error);
+        assert!(error.to_string().contains("HTTP request failed"));
+        assert!(!message.contains("secret-api-key"));
+    }
+
+    #[tokio::test]
+    async fn test_send_through_transport() {
+        let transport = RecordingTransport::new();
+        let client = recording_client(&transport);
+
+        client.send("Hello 😀 world ✅").await.unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].user, "12345678");
+        assert_eq!(requests[0].message, "Hello [] world ✅");
+    }
+
+    #[tokio::test]
+    async fn test_send_chunks_through_transport() {
+        let transport = RecordingTransport::new();
+        let client = recording_client(&transport);
+
+        client.send(&"lorem ipsum ".repeat(100)).await.unwrap();
+
+        let messages = transport.messages();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].starts_with("[1/2] lorem"));
+        assert!(messages[1].starts_with("[2/2] "));
+    }
+
+    #[tokio::test]
+    async fn test_send_maps_error_status() {
+        let transport = RecordingTransport::with_responses([403]);
+        let client = recording_client(&transport);
+
+        let result = client.send(&"lorem ipsum ".repeat(100)).await;
+
+        assert!(matches!(result, Err(FreeMobileError::AccessDenied)));
+        // The second chunk is not sent once the first one failed
+        assert_eq!(transport.messages().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_retries_retryable_errors() {
+        let transport = RecordingTransport::with_responses([500, 402]);
+        let client = recording_client(&transport);
+
+        let report = client.send("Night alert ⚠️").await.unwrap();
+
+        assert_eq!(report.total_attempts(), 3);
+        assert_eq!(report.chunks[0].attempts, 3);
+        assert_eq!(report.chunks[0].status, Some(200));
+        assert_eq!(transport.messages(), vec!["Night alert ⚠️"; 3]);
+    }
+
+    #[tokio::test]
+    async fn test_send_does_not_retry_non_retryable_errors() {
+        let transport = RecordingTransport::with_responses([400]);
+        let client = recording_client(&transport);
+
+        let result = client.send("Night alert").await;
+
+        assert!(matches!(result, Err(FreeMobileError::InvalidCredentials)));
+        assert_eq!(transport.messages().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_reports_exhausted_retries() {
+        let transport = RecordingTransport::with_responses([500, 500, 500]);
+        let client = recording_client(&transport);
+
+        let result = client.send("Night alert").await;
+
+        match result {
+            Err(FreeMobileError::RetriesExhausted { attempts, source }) => {
+                assert_eq!(attempts, 3);
+                assert!(matches!(*source, FreeMobileError::ServerError));
+            }
+            other => panic!("Expected RetriesExhausted, got {:?}", other),
+        }
+        assert_eq!(transport.messages().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_send_report() {
+        let transport = RecordingTransport::new();
+        let client = recording_client(&transport);
+
+        let report = client.send(&"lorem ipsum ".repeat(100)).await.unwrap();
+
+        assert!(report.is_complete());
+        assert_eq!(report.chunks.len(), 2);
+        assert_eq!(report.chunks[1].index, 1);
+        assert_eq!(report.chunks[1].status, Some(200));
+        assert_eq!(report.chunks[1].attempts, 1);
+        assert_eq!(
+            report
+                .chunks
+                .iter()
+                .map(|c| c.text.clone())
+                .collect::<Vec<_>>(),
+            transport.messages()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_send_partial_failure_report() {
+        let transport = RecordingTransport::with_responses([200, 403]);
+        let client = recording_client(&transport);
+
+        let result = client.send(&"lorem ipsum ".repeat(200)).await;
+
+        match result {
+            Err(FreeMobileError::PartialSend { report, source }) => {
+                assert!(matches!(*source, FreeMobileError::AccessDenied));
+                assert_eq!(report.chunks.len(), 3);
+                assert_eq!(report.sent_count(), 1);
+                assert_eq!(report.first_unsent_index(), Some(1));
+                assert_eq!(report.chunks[1].status, Some(403));
+                assert_eq!(report.chunks[1].body.as_deref(), Some(""));
+                assert!(matches!(report.chunks[1].outcome, ChunkOutcome::Failed(_)));
+                assert_eq!(report.chunks[2].outcome, ChunkOutcome::NotSent);
+                assert_eq!(report.chunks[2].attempts, 0);
+            }
+            other => panic!("Expected PartialSend, got {:?}", other),
+        }
+        assert_eq!(transport.messages().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_resume_keeps_original_numbering() {
+        let transport = RecordingTransport::new();
+        let client = recording_client(&transport);
+        let plan = SendPlan::new(&"lorem ipsum ".repeat(200));
+
+        let report = client.resume(&plan, 1).await.unwrap();
+
+        assert_eq!(transport.messages(), plan.chunks[1..].to_vec());
+        assert!(transport.messages()[0].starts_with("[2/3] "));
+        assert_eq!(report.chunks.len(), 2);
+        assert_eq!(report.chunks[0].index, 1);
+    }
+
+    #[tokio::test]
+    async fn test_resume_after_partial_failure() {
+        let transport = RecordingTransport::with_responses([200, 403]);
+        let client = recording_client(&transport);
+
+        let report = match client.send(&"lorem ipsum ".repeat(200)).await {
+            Err(FreeMobileError::PartialSend { report, .. }) => report,
+            other => panic!("Expected PartialSend, got {:?}", other),
+        };
+        transport.clear();
+
+        let plan = SendPlan::from_report(&report);
+        let from_index = report.first_unsent_index().unwrap();
+        let resumed = client.resume(&plan, from_index).await.unwrap();
+
+        assert!(resumed.is_complete());
+        assert_eq!(transport.messages(), plan.chunks[1..].to_vec());
+    }
+
+    #[tokio::test]
+    async fn test_resume_out_of_range() {
+        let transport = RecordingTransport::new();
+        let client = recording_client(&transport);
+        let plan = SendPlan::new("Hello");
+
+        assert!(client.resume(&plan, 1).await.unwrap().chunks.is_empty());
+        assert!(matches!(
+            client.resume(&plan, 2).await,
+            Err(FreeMobileError::InvalidMessage(_))
+        ));
+        assert!(transport.messages().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_throttles_sends() {
+        let transport = RecordingTransport::new();
+        let creds = Credentials::new("12345678".to_string(), "abcdef123".to_string());
+        let client = FreeMobileClient::builder(creds)
+            .chunk_delay(Duration::ZERO)
+            .rate_limiter(RateLimiter::new(1, Duration::from_millis(50)))
+            .build_with_transport(transport.clone())
+            .unwrap();
+
+        let started = Instant::now();
+        let (first, second) = tokio::join!(client.send("first"), client.send("second"));
+
+        assert!(first.is_ok() && second.is_ok());
+        assert_eq!(transport.messages().len(), 2);
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn test_send_keeps_unexpected_status_body() {
+        let transport = RecordingTransport::new();
+        transport.push_response_with_body(404, "Not Found");
+        let client = recording_client(&transport);
+
+        let result = client.send("Night alert").await;
+
+        match result {
+            Err(FreeMobileError::UnexpectedStatus { status, body }) => {
+                assert_eq!(status, 404);
+                assert_eq!(body, "Not Found");
+            }
+            other => panic!("Expected UnexpectedStatus, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_send_retries_gateway_errors() {
+        let transport = RecordingTransport::new();
+        transport.push_response_with_body(502, "Bad Gateway");
+        transport.push_response_with_body(503, "Service Unavailable");
+        let client = recording_client(&transport);
+
+        let report = client.send("Night alert").await.unwrap();
+
+        assert_eq!(report.chunks[0].attempts, 3);
+        assert_eq!(report.chunks[0].body, None);
+    }
+
+    #[tokio::test]
+    async fn test_send_without_retries() {
+        let transport = RecordingTransport::with_responses([500]);
+        let creds = Credentials::new("12345678".to_string(), "abcdef123".to_string());
+        let client = FreeMobileClient::builder(creds)
+            .retry_policy(RetryPolicy::none())
+            .build_with_transport(transport.clone())
+            .unwrap();
+
+        let result = client.send("Night alert").await;
+
+        assert!(matches!(result, Err(FreeMobileError::ServerError)));
+        assert_eq!(transport.messages().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_send_empty_message_not_sent() {
+        let transport = RecordingTransport::new();
+        let client = recording_client(&transport);
+
+        let result = client.send("   ").await;
+
+        assert!(matches!(result, Err(FreeMobileError::EmptyMessage)));
+        assert!(transport.messages().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_custom_chunk_format() {
+        let transport = RecordingTransport::new();
+        let client = FreeMobileClient::builder(Credentials::new(
+            "12345678".to_string(),
+            "abcdef123".to_string(),
+        ))
+        .chunk_delay(Duration::ZERO)
+        .rate_limiter(RateLimiter::unlimited())
+        .chunk_format(ChunkFormat {
+            template: "({i}/{n})".to_string(),
+            placement: MarkerPlacement::Suffix,
+            header: Some("web-01".to_string()),
+        })
+        .build_with_transport(transport.clone())
+        .unwrap();
+
+        client.send(&"lorem ipsum ".repeat(100)).await.unwrap();
+
+        let messages = transport.messages();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[0].starts_with("web-01 lorem"));
+        assert!(messages[0].ends_with(" (1/2)"));
+        assert!(messages[1].ends_with(" (2/2)"));
+    }
+
+    #[tokio::test]
+    async fn test_sanitize_policy() {
+        let transport = RecordingTransport::new();
+        let client = FreeMobileClient::builder(Credentials::new(
+            "12345678".to_string(),
+            "abcdef123".to_string(),
+        ))
+        .sanitize_policy(SanitizePolicy::Closest)
+        .build_with_transport(transport.clone())
+        .unwrap();
+
+        let plan = client.plan("🔥 disk full").unwrap();
+        assert_eq!(plan.sanitized, "⚠️ disk full");
+        assert_eq!(plan.replacements[0].replacement, "⚠️");
+
+        client.send("🔥 disk full").await.unwrap();
+        assert_eq!(transport.messages(), vec!["⚠️ disk full"]);
+    }
+
+    #[tokio::test]
+    async fn test_emoji_table() {
+        let transport = RecordingTransport::new();
+        let mut table = EmojiTable::builtin();
+        table.add_supported("🫠");
+        table.add_substitution("🚀", "(rocket)");
+        let client = FreeMobileClient::builder(Credentials::new(
+            "12345678".to_string(),
+            "abcdef123".to_string(),
+        ))
+        .emoji_table(table)
+        .build_with_transport(transport.clone())
+        .unwrap();
+
+        assert_eq!(client.sanitize_message("🫠 🚀 😀"), "🫠 (rocket) []");
+        client.send("🫠 🚀").await.unwrap();
+        assert_eq!(transport.messages(), vec!["🫠 (rocket)"]);
+    }
+
+    #[test]
+    fn test_sanitize_options() {
+        let build = |options| {
+            FreeMobileClient::builder(Credentials::new(
+                "12345678".to_string(),
+                "abcdef123".to_string(),
+            ))
+            .sanitize_options(options)
+            .sanitize_policy(SanitizePolicy::Drop)
+            .build_with_transport(RecordingTransport::new())
+            .unwrap()
+        };
+
+        let client = build(SanitizeOptions::default());
+        assert_eq!(
+            client.sanitize_message("\x1B[31m🚀 down\x1B[0m\x07"),
+            " down"
+        );
+
+        let client = build(SanitizeOptions {
+            strip_ansi: false,
+            controls: ControlChars::Keep,
+            ..SanitizeOptions::default()
+        });
+        let plan = client.plan("\x1B[31m🚀 down\x1B[0m").unwrap();
+        assert_eq!(plan.sanitized, "\x1B[31m down\x1B[0m");
+        assert_eq!(plan.replacements.len(), 1);
+    }
+
+    #[test]
+    fn test_split_strategy() {
+        let client = FreeMobileClient::builder(Credentials::new(
+            "12345678".to_string(),
+            "abcdef123".to_string(),
+        ))
+        .split(SplitStrategy::Sentence)
+        .build_with_transport(RecordingTransport::new())
+        .unwrap();
+
+        let plan = client
+            .plan(&"This sentence ends with a full stop. ".repeat(60))
+            .unwrap();
+        assert!(plan.chunks.len() > 1);
+        assert!(plan.chunks.iter().all(|chunk| chunk.ends_with("stop.")));
+    }
+
+    #[tokio::test]
+    async fn test_max_chunks() {
+        let transport = RecordingTransport::new();
+        let client = FreeMobileClient::builder(Credentials::new(
+            "12345678".to_string(),
+            "abcdef123".to_string(),
+        ))
+        .chunk_delay(Duration::ZERO)
+        .rate_limiter(RateLimiter::unlimited())
+        .max_chunks(2, Overflow::Truncate)
+        .build_with_transport(transport.clone())
+        .unwrap();
+
+        client.send(&"lorem ipsum ".repeat(500)).await.unwrap();
+        let messages = transport.messages();
+        assert_eq!(messages.len(), 2);
+        assert!(messages[1].starts_with("[2/2] ") && messages[1].ends_with('…'));
+
+        let refusing = FreeMobileClient::builder(Credentials::new(
+            "12345678".to_string(),
+            "abcdef123".to_string(),
+        ))
+        .max_chunks(1, Overflow::Refuse)
+        .build_with_transport(transport.clone())
+        .unwrap();
+        assert!(matches!(
+            refusing.send(&"lorem ipsum ".repeat(500)).await,
+            Err(FreeMobileError::TooManyChunks { max: 1, .. })
+        ));
+        assert_eq!(transport.messages().len(), 2);
+
+        let result = FreeMobileClient::builder(Credentials::new(
+            "12345678".to_string(),
+            "abcdef123".to_string(),
+        ))
+        .max_chunks(0, Overflow::Refuse)
+        .build_with_transport(RecordingTransport::new());
+        assert!(matches!(result, Err(FreeMobileError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_chunk_format_without_room() {
+        let result = FreeMobileClient::builder(Credentials::new(
+            "12345678".to_string(),
+            "abcdef123".to_string(),
+        ))
+        .chunk_format(ChunkFormat {
+            header: Some("x".repeat(MAX_MESSAGE_LENGTH)),
+            ..ChunkFormat::default()
+        })
+        .build_with_transport(RecordingTransport::new());
+
+        assert!(matches!(result, Err(FreeMobileError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_plan_does_not_send() {
+        let transport = RecordingTransport::new();
+        let client = recording_client(&transport);
+
+        let plan = client.plan(&"lorem ipsum 😀 ".repeat(80)).unwrap();
+
+        assert_eq!(plan.len(), 2);
+        assert_eq!(plan.replacements.len(), 80);
+        assert!(transport.messages().is_empty());
+        assert!(matches!(
+            client.plan(" "),
+            Err(FreeMobileError::EmptyMessage)
+        ));
+    }
+
     #[test]
     fn test_sanitization_integration() {
         let creds = Credentials::new("user".to_string(), "pass".to_string());
@@ -7,20 +7,68 @@
 pub const MAX_MESSAGE_LENGTH: usize = 999;
 
 /// Number of characters to reserve for chunk prefixes like "[99/99] "
+#[deprecated(
+    note = "the reserve is computed from the chunk format, see `ChunkFormat::reserved_length`"
+)]
 pub const PREFIX_RESERVE_LENGTH: usize = 8;
 
+/// Default part marker of multi-part messages, `{i}` is the part number and `{n}` the number of parts
+pub const DEFAULT_CHUNK_TEMPLATE: &str = "[{i}/{n}]";
+
+/// Default text replacing emojis FreeMobile cannot render
+pub const EMOJI_PLACEHOLDER: &str = "[]";
+
 /// HTTP request timeout for API calls
 pub const REQUEST_TIMEOUT_SECS: u64 = 30;
 
 /// Delay between consecutive chunk sends to respect rate limits
 pub const CHUNK_DELAY_MS: u64 = 500;
 
+/// Maximum number of bytes of an error response body kept for diagnostics
+pub const MAX_ERROR_BODY_LENGTH: usize = 1024;
+
 /// User agent string for HTTP requests
 pub const USER_AGENT: &str = "freemobile-api/0.1.0";
 
 /// FreeMobile API endpoint URL
 pub const API_URL: &str = "https://smsapi.free-mobile.fr/sendmsg";
 
+/// Markers of messages shortened to their maximum number of chunks
+pub mod overflow {
+    /// Appended to the last chunk of a truncated message
+    pub const TRUNCATION_MARKER: &str = "…";
+
+    /// Inserted where the middle of a message was dropped, `{n}` is the number of omitted characters
+    pub const OMISSION_MARKER: &str = "[… {n} chars omitted …]";
+}
+
+/// Retry settings applied to failed chunk sends
+pub mod retry {
+    /// Total number of attempts per chunk, including the first one
+    pub const MAX_ATTEMPTS: u32 = 3;
+
+    /// Delay before the first retry, doubled for each following retry
+    pub const BASE_DELAY_MS: u64 = 1_000;
+
+    /// Upper bound of the exponential backoff delay
+    pub const MAX_DELAY_MS: u64 = 30_000;
+
+    /// Share of the backoff delay randomly removed to spread retries
+    pub const JITTER_RATIO: f64 = 0.2;
+
+    /// Minimum delay before retrying after a rate limit error (HTTP 402)
+    pub const RATE_LIMIT_COOLDOWN_MS: u64 = 10_000;
+}
+
+/// Client-side rate limiting of outgoing requests
+pub mod rate_limit {
+    /// Number of requests that can be sent at once before being throttled
+    pub const BURST: u32 = 5;
+
+    /// Delay after which one more request is allowed
+    pub const REFILL_INTERVAL_MS: u64 = 1_000;
+}
+
 /// HTTP status codes returned by FreeMobile API
 pub mod status_codes {
     /// Invalid credentials - check user ID and API key
@@ -45,6 +93,26 @@ pub mod word_boundary {
     /// (e.g., don't split too close to beginning: 1/3 = 33%)
     pub const MIN_BOUNDARY_RATIO: usize = 3;
 
+    /// Maximum number of graphemes left unused at the end of a chunk to split it
+    /// at a preferred boundary (paragraph, line or sentence) instead of whitespace
+    pub const SPLIT_TOLERANCE: usize = 200;
+
     /// Minimum word recognition ratio for chunk quality validation
     pub const MIN_WORD_RECOGNITION_RATIO: f64 = 0.8;
 }
+
+/// Segment sizes of the SMS delivered by the operator (3GPP TS 23.038)
+pub mod encoding {
+    /// Septets of a single-segment GSM-7 message
+    pub const GSM7_SINGLE_SEPTETS: usize = 160;
+
+    /// Septets of each segment of a multi-segment GSM-7 message,
+    /// the rest of the segment holding the concatenation header
+    pub const GSM7_SEGMENT_SEPTETS: usize = 153;
+
+    /// UTF-16 code units of a single-segment UCS-2 message
+    pub const UCS2_SINGLE_UNITS: usize = 70;
+
+    /// UTF-16 code units of each segment of a multi-segment UCS-2 message
+    pub const UCS2_SEGMENT_UNITS: usize = 67;
+}
@@ -0,0 +1,241 @@
+//! Emojis kept or substituted by the sanitizer
+//!
+//! The built-in table holds `SUPPORTED_EMOJIS`. It can be extended at runtime
+//! with a TOML file listing additional supported emojis and substitutions:
+//!
+//! ```toml
+//! # Glyphs FreeMobile started to render
+//! supported = ["🫠", "🪿"]
+//!
+//! # Replacements of unsupported emojis, taking precedence over the sanitize policy
+//! [substitutions]
+//! "🔥" = "⚠️"
+//! "🚀" = "(rocket)"
+//! ```
+
+use crate::error::FreeMobileError;
+use crate::supported_emojis::SUPPORTED_EMOJIS;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+static BUILTIN: LazyLock<EmojiTable> = LazyLock::new(|| EmojiTable {
+    supported: SUPPORTED_EMOJIS
+        .iter()
+        .map(|emoji| normalize(emoji))
+        .collect(),
+    substitutions: HashMap::new(),
+});
+
+/// Content of an emoji table file
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EmojiTableFile {
+    #[serde(default)]
+    supported: Vec<String>,
+    #[serde(default)]
+    substitutions: HashMap<String, String>,
+}
+
+/// Supported emojis and substitutions used by the sanitizer
+///
+/// Lookups accept emojis with or without their variation selector (U+FE0F).
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::EmojiTable;
+///
+/// let table = EmojiTable::builtin().merge_toml(
+///     r#"
+///     supported = ["🫠"]
+///
+///     [substitutions]
+///     "🔥" = "⚠️"
+///     "#,
+/// )?;
+/// assert!(table.is_supported("🫠"));
+/// assert!(table.is_supported("✅"));
+/// assert_eq!(table.substitution("🔥"), Some("⚠️"));
+/// # Ok::<(), freemobile_api::FreeMobileError>(())
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EmojiTable {
+    supported: HashSet<String>,
+    substitutions: HashMap<String, String>,
+}
+
+impl Default for EmojiTable {
+    fn default() -> Self {
+        Self::builtin()
+    }
+}
+
+impl EmojiTable {
+    /// Table of the emojis supported by FreeMobile, without substitutions
+    pub fn builtin() -> Self {
+        BUILTIN.clone()
+    }
+
+    /// Shared built-in table, avoiding a copy for each message
+    pub(crate) fn builtin_ref() -> &'static Self {
+        &BUILTIN
+    }
+
+    /// Built-in table extended with a TOML file
+    ///
+    /// # Errors
+    ///
+    /// Returns `FreeMobileError::IoError` if the file cannot be read, or
+    /// `FreeMobileError::ConfigError` if it is not a valid emoji table.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, FreeMobileError> {
+        let content = fs::read_to_string(path.as_ref()).map_err(FreeMobileError::IoError)?;
+        Self::builtin().merge_toml(&content).map_err(|e| match e {
+            FreeMobileError::ConfigError(message) => {
+                FreeMobileError::ConfigError(format!("{}: {}", path.as_ref().display(), message))
+            }
+            e => e,
+        })
+    }
+
+    /// Add the supported emojis and substitutions of a TOML table
+    ///
+    /// Substitutions of the TOML table replace existing ones for the same emoji.
+    ///
+    /// # Errors
+    ///
+    /// Returns `FreeMobileError::ConfigError` if `content` is not a valid emoji table.
+    pub fn merge_toml(mut self, content: &str) -> Result<Self, FreeMobileError> {
+        let file: EmojiTableFile = toml::from_str(content).map_err(|e| {
+            FreeMobileError::ConfigError(format!("Invalid emoji table: {}", e.message()))
+        })?;
+
+        self.supported
+            .extend(file.supported.iter().map(|emoji| normalize(emoji)));
+        self.substitutions.extend(
+            file.substitutions
+                .into_iter()
+                .map(|(emoji, substitute)| (normalize(&emoji), substitute)),
+        );
+        Ok(self)
+    }
+
+    /// Mark an emoji as supported
+    pub fn add_supported(&mut self, emoji: &str) {
+        self.supported.insert(normalize(emoji));
+    }
+
+    /// Replace an emoji with `substitute` when sanitizing
+    pub fn add_substitution(&mut self, emoji: &str, substitute: impl Into<String>) {
+        self.substitutions
+            .insert(normalize(emoji), substitute.into());
+    }
+
+    /// Check if FreeMobile renders an emoji
+    pub fn is_supported(&self, emoji: &str) -> bool {
+        self.supported.contains(&normalize(emoji))
+    }
+
+    /// Text replacing an emoji, if the table has a substitution for it
+    pub fn substitution(&self, emoji: &str) -> Option<&str> {
+        self.substitutions
+            .get(&normalize(emoji))
+            .map(String::as_str)
+    }
+}
+
+/// Emoji without its variation selector
+fn normalize(emoji: &str) -> String {
+    emoji.replace('\u{FE0F}', "")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    #[test]
+    fn test_builtin() {
+        let table = EmojiTable::builtin();
+
+        assert!(table.is_supported("✅"));
+        assert!(table.is_supported("⚡️"));
+        assert!(table.is_supported("✔️"));
+        assert!(!table.is_supported("🚀"));
+        assert_eq!(table.substitution("🚀"), None);
+        assert_eq!(EmojiTable::default(), table);
+    }
+
+    #[test]
+    fn test_merge_toml() {
+        let table = EmojiTable::builtin()
+            .merge_toml(
+                r#"
+                supported = ["🫠"]
+
+                [substitutions]
+                "🔥" = "⚠️"
+                "🌡️" = "temp"
+                "#,
+            )
+            .unwrap();
+
+        assert!(table.is_supported("🫠"));
+        assert!(table.is_supported("✅"));
+        assert_eq!(table.substitution("🔥"), Some("⚠️"));
+        assert_eq!(table.substitution("🌡"), Some("temp"));
+        assert_eq!(table.substitution("🌡️"), Some("temp"));
+    }
+
+    #[test]
+    fn test_invalid_toml() {
+        for content in ["supported = \"🫠\"", "unknown = []", "[substitutions"] {
+            assert!(matches!(
+                EmojiTable::builtin().merge_toml(content),
+                Err(FreeMobileError::ConfigError(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_load() {
+        let mut file = NamedTempFile::new().unwrap();
+        writeln!(file, "[substitutions]\n\"🚀\" = \"(rocket)\"").unwrap();
+
+        let table = EmojiTable::load(file.path()).unwrap();
+        assert_eq!(table.substitution("🚀"), Some("(rocket)"));
+        assert!(matches!(
+            EmojiTable::load("/nonexistent/emojis.toml"),
+            Err(FreeMobileError::IoError(_))
+        ));
+    }
+
+    #[test]
+    fn test_add_entries() {
+        let mut table = EmojiTable::builtin();
+        table.add_supported("🪿");
+        table.add_substitution("🚀", "rocket");
+
+        assert!(table.is_supported("🪿"));
+        assert_eq!(table.substitution("🚀"), Some("rocket"));
+    }
+
+    #[test]
+    fn test_supported_variation_selector() {
+        assert!(!EmojiTable::builtin().is_supported("🏔"));
+
+        let table = EmojiTable::builtin()
+            .merge_toml("supported = [\"🏔\u{FE0F}\"]")
+            .unwrap();
+        assert!(table.is_supported("🏔"));
+        assert!(table.is_supported("🏔\u{FE0F}"));
+
+        let mut table = EmojiTable::builtin();
+        table.add_supported("🕊\u{FE0F}");
+        assert!(table.is_supported("🕊"));
+        assert!(table.is_supported("🕊\u{FE0F}"));
+    }
+}
@@ -0,0 +1,371 @@
+//! GSM-7 and UCS-2 encoding analysis
+//!
+//! FreeMobile accepts up to `MAX_MESSAGE_LENGTH` characters per request, but the
+//! operator delivers them as SMS segments encoded in GSM-7 (160 septets) or, as
+//! soon as a single character is missing from the GSM-7 alphabet, in UCS-2
+//! (70 UTF-16 code units). This module tells which encoding a message gets and
+//! how many segments it takes.
+
+use crate::constants::encoding::{
+    GSM7_SEGMENT_SEPTETS, GSM7_SINGLE_SEPTETS, UCS2_SEGMENT_UNITS, UCS2_SINGLE_UNITS,
+};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Basic character set of the GSM 03.38 alphabet, one septet each
+const GSM7_BASIC: &str = "@£$¥èéùìòÇ\nØø\rÅåΔ_ΦΓΛΩΠΨΣΘΞÆæßÉ !\"#¤%&'()*+,-./0123456789:;<=>?\
+¡ABCDEFGHIJKLMNOPQRSTUVWXYZÄÖÑÜ§¿abcdefghijklmnopqrstuvwxyzäöñüà";
+
+/// Extension table of the GSM 03.38 alphabet, two septets each (escape and character)
+const GSM7_EXTENSION: &str = "\x0C^{}\\[~]|€";
+
+/// GSM-7 equivalents of typographic characters, spaces and letters without
+/// a diacritic to drop
+const GSM7_TRANSLITERATIONS: &[(&str, &str)] = &[
+    ("‘’‚‛′´`", "'"),
+    ("“”„‟″«»", "\""),
+    ("‐‑‒–—―−", "-"),
+    ("…", "..."),
+    (
+        "\t\u{A0}\u{2000}\u{2001}\u{2002}\u{2003}\u{2004}\u{2005}\u{2006}\u{2007}\u{2008}\u{2009}\u{200A}\u{202F}\u{205F}\u{3000}",
+        " ",
+    ),
+    ("œ", "oe"),
+    ("Œ", "OE"),
+    ("ł", "l"),
+    ("Ł", "L"),
+    ("đ", "d"),
+    ("Đ", "D"),
+    ("ı", "i"),
+];
+
+/// Encoding the operator delivers a message with
+///
+/// Encodings are ordered from the one fitting the most characters per segment
+/// to the one fitting the fewest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Encoding {
+    /// GSM-7 basic character set only
+    Gsm7,
+    /// GSM-7 with characters of the extension table, e.g. `€` or `[`
+    Gsm7Extended,
+    /// UCS-2, required by any character outside of the GSM-7 alphabet
+    Ucs2,
+}
+
+impl fmt::Display for Encoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Gsm7 => "GSM-7",
+            Self::Gsm7Extended => "GSM-7 with extension table",
+            Self::Ucs2 => "UCS-2",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Septets of a character in GSM-7, or `None` if it requires UCS-2
+fn gsm7_septets(c: char) -> Option<usize> {
+    if GSM7_BASIC.contains(c) {
+        Some(1)
+    } else if GSM7_EXTENSION.contains(c) {
+        Some(2)
+    } else {
+        None
+    }
+}
+
+/// Check if a character is part of the GSM-7 alphabet, extension table included
+pub fn is_gsm7(c: char) -> bool {
+    gsm7_septets(c).is_some()
+}
+
+/// GSM-7 equivalent of a grapheme missing from the GSM-7 alphabet
+///
+/// Typographic quotes, dashes, ellipses and special spaces get their plain
+/// equivalent, and letters lose the diacritics GSM-7 lacks (`ê` becomes `e`)
+/// while the accented letters of the alphabet (`é`, `è`, `à`, `ù`...) are kept.
+/// Returns `None` for graphemes already in GSM-7 or without equivalent, such
+/// as emojis.
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::encoding::to_gsm7;
+///
+/// assert_eq!(to_gsm7("—").as_deref(), Some("-"));
+/// assert_eq!(to_gsm7("ê").as_deref(), Some("e"));
+/// assert_eq!(to_gsm7("é"), None);
+/// assert_eq!(to_gsm7("🚀"), None);
+/// ```
+pub fn to_gsm7(grapheme: &str) -> Option<String> {
+    if grapheme.chars().all(is_gsm7) {
+        return None;
+    }
+
+    // Decomposed letters may be in the alphabet once composed, e.g. e + U+0301
+    let composed: String = grapheme.nfc().collect();
+    let mut transliterated = String::new();
+    for c in composed.chars() {
+        if is_gsm7(c) {
+            transliterated.push(c);
+        } else if let Some((_, equivalent)) = GSM7_TRANSLITERATIONS
+            .iter()
+            .find(|(chars, _)| chars.contains(c))
+        {
+            transliterated.push_str(equivalent);
+        } else if !is_combining_mark(c) {
+            let base: String = c.nfd().filter(|&c| !is_combining_mark(c)).collect();
+            if base.is_empty() || !base.chars().all(is_gsm7) {
+                return None;
+            }
+            transliterated.push_str(&base);
+        }
+    }
+    Some(transliterated)
+}
+
+/// Number of segments of a message, units of a character never being split
+fn segment_count(
+    units: impl Iterator<Item = usize>,
+    total: usize,
+    single: usize,
+    segment: usize,
+) -> usize {
+    if total == 0 {
+        return 0;
+    }
+    if total <= single {
+        return 1;
+    }
+
+    let mut segments = 1;
+    let mut used = 0;
+    for width in units {
+        if used + width > segment {
+            segments += 1;
+            used = 0;
+        }
+        used += width;
+    }
+    segments
+}
+
+/// Encoding, length and segments of a message as delivered by the operator
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::{Encoding, EncodingReport};
+///
+/// let report = EncodingReport::analyze("Température: 12€");
+/// assert_eq!(report.encoding, Encoding::Gsm7Extended);
+/// assert_eq!(report.units, 17);
+/// assert_eq!(report.segments, 1);
+///
+/// let report = EncodingReport::analyze("Ça chauffe ✅");
+/// assert_eq!(report.encoding, Encoding::Ucs2);
+/// assert_eq!(report.ucs2_chars, vec!["✅"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EncodingReport {
+    pub encoding: Encoding,
+    /// Length of the message: septets in GSM-7, extension characters counting
+    /// twice, or UTF-16 code units in UCS-2
+    pub units: usize,
+    /// Number of SMS segments the message is delivered in
+    pub segments: usize,
+    /// Characters forcing UCS-2, without duplicates and in order of appearance
+    pub ucs2_chars: Vec<String>,
+}
+
+impl EncodingReport {
+    /// Analyze the encoding of a message
+    pub fn analyze(message: &str) -> Self {
+        let mut ucs2_chars: Vec<String> = Vec::new();
+        for grapheme in message.graphemes(true) {
+            if grapheme.chars().any(|c| gsm7_septets(c).is_none())
+                && !ucs2_chars.iter().any(|known| known == grapheme)
+            {
+                ucs2_chars.push(grapheme.to_string());
+            }
+        }
+
+        if ucs2_chars.is_empty() {
+            let septets = || message.chars().filter_map(gsm7_septets);
+            let units = septets().sum();
+            let encoding = if units > message.chars().count() {
+                Encoding::Gsm7Extended
+            } else {
+                Encoding::Gsm7
+            };
+            Self {
+                encoding,
+                units,
+                segments: segment_count(
+                    septets(),
+                    units,
+                    GSM7_SINGLE_SEPTETS,
+                    GSM7_SEGMENT_SEPTETS,
+                ),
+                ucs2_chars,
+            }
+        } else {
+            let code_units = || message.chars().map(char::len_utf16);
+            let units = code_units().sum();
+            Self {
+                encoding: Encoding::Ucs2,
+                units,
+                segments: segment_count(code_units(), units, UCS2_SINGLE_UNITS, UCS2_SEGMENT_UNITS),
+                ucs2_chars,
+            }
+        }
+    }
+
+    /// Check if the message is delivered in UCS-2
+    pub fn is_ucs2(&self) -> bool {
+        self.encoding == Encoding::Ucs2
+    }
+}
+
+impl fmt::Display for EncodingReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let units = if self.is_ucs2() {
+            "code units"
+        } else {
+            "septets"
+        };
+        let segments = if self.segments == 1 {
+            "segment"
+        } else {
+            "segments"
+        };
+        write!(
+            f,
+            "{}, {} {}, {} {}",
+            self.encoding, self.units, units, self.segments, segments
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_gsm7_basic() {
+        let report = EncodingReport::analyze("Hello @home, café à 10$ ?");
+
+        assert_eq!(report.encoding, Encoding::Gsm7);
+        assert_eq!(report.units, 25);
+        assert_eq!(report.segments, 1);
+        assert!(report.ucs2_chars.is_empty());
+        assert_eq!(report.to_string(), "GSM-7, 25 septets, 1 segment");
+    }
+
+    #[test]
+    fn test_gsm7_extension_counts_twice() {
+        let report = EncodingReport::analyze("{a|b} ~ [€]");
+
+        assert_eq!(report.encoding, Encoding::Gsm7Extended);
+        assert_eq!(report.units, 11 + 7);
+    }
+
+    #[test]
+    fn test_ucs2_forced() {
+        let report = EncodingReport::analyze("École: 🚀 ok 🚀, ÀÉ");
+
+        assert_eq!(report.encoding, Encoding::Ucs2);
+        assert!(report.is_ucs2());
+        assert_eq!(report.ucs2_chars, vec!["🚀", "À"]);
+        // The rocket is a surrogate pair
+        assert_eq!(report.units, 17 + 2);
+        assert_eq!(report.to_string(), "UCS-2, 19 code units, 1 segment");
+
+        let tab = EncodingReport::analyze("a\tb");
+        assert_eq!(tab.ucs2_chars, vec!["\t"]);
+    }
+
+    #[test]
+    fn test_gsm7_segments() {
+        let segments = |length| EncodingReport::analyze(&"a".repeat(length)).segments;
+
+        assert_eq!(segments(0), 0);
+        assert_eq!(segments(160), 1);
+        assert_eq!(segments(161), 2);
+        assert_eq!(segments(306), 2);
+        assert_eq!(segments(307), 3);
+    }
+
+    #[test]
+    fn test_extension_char_not_split() {
+        // 152 septets then an extension character does not fit the first segment
+        let message = format!("{}€{}", "a".repeat(152), "a".repeat(10));
+        let report = EncodingReport::analyze(&message);
+
+        assert_eq!(report.units, 164);
+        assert_eq!(report.segments, 2);
+        let message = format!("{}€{}", "a".repeat(152), "a".repeat(152));
+        assert_eq!(EncodingReport::analyze(&message).segments, 3);
+    }
+
+    #[test]
+    fn test_ucs2_segments() {
+        let segments = |message: String| EncodingReport::analyze(&message).segments;
+
+        assert_eq!(segments("é".repeat(70) + "✅"), 2);
+        assert_eq!(segments("✅".repeat(70)), 1);
+        assert_eq!(segments("✅".repeat(71)), 2);
+        assert_eq!(segments("✅".repeat(134)), 2);
+        assert_eq!(segments("✅".repeat(135)), 3);
+        // 66 units then a surrogate pair does not fit the first segment
+        assert_eq!(segments("✅".repeat(66) + "🚀" + &"✅".repeat(66)), 3);
+    }
+
+    #[test]
+    fn test_to_gsm7() {
+        let transliterate = |message: &str| -> String {
+            message
+                .graphemes(true)
+                .map(|grapheme| to_gsm7(grapheme).unwrap_or_else(|| grapheme.to_string()))
+                .collect()
+        };
+
+        assert_eq!(
+            transliterate("« L’alerte — c’est “grave”… »"),
+            "\" L'alerte - c'est \"grave\"... \""
+        );
+        assert_eq!(transliterate("a\u{A0}b\u{202F}!\tc"), "a b ! c");
+        assert_eq!(
+            transliterate("Dégâts à Île-de-France, ça brûle"),
+            "Dégats à Ile-de-France, ca brule"
+        );
+        assert_eq!(
+            transliterate("Łódź, Œuvre, Ærø, Müller"),
+            "Lodz, OEuvre, Ærø, Müller"
+        );
+        assert_eq!(transliterate("e\u{301}te\u{302}"), "éte");
+        assert_eq!(transliterate("∞ 🚀 ✅"), "∞ 🚀 ✅");
+
+        let message = "Température élevée… à 12 °C – vérifiez “srv-01”";
+        assert!(EncodingReport::analyze(message).is_ucs2());
+        assert_eq!(
+            EncodingReport::analyze(&transliterate(message)).ucs2_chars,
+            vec!["°"]
+        );
+    }
+
+    #[test]
+    fn test_serialize() {
+        let json = serde_json::to_value(EncodingReport::analyze("{}")).unwrap();
+        assert_eq!(json["encoding"], "gsm7-extended");
+        assert_eq!(
+            serde_json::to_value(Encoding::Ucs2).unwrap(),
+            serde_json::json!("ucs2")
+        );
+    }
+}
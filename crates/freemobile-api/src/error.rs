@@ -1,7 +1,9 @@
+use crate::report::SendReport;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum FreeMobileError {
+    /// HTTP 400: the API rejected the user ID or API key
     #[error("Invalid credentials provided")]
     InvalidCredentials,
 
@@ -14,8 +16,12 @@ pub enum FreeMobileError {
     #[error("FreeMobile server error")]
     ServerError,
 
+    #[error("Unexpected HTTP status {status}{}", body_excerpt(.body))]
+    UnexpectedStatus { status: u16, body: String },
+
+    /// HTTP failure, without the request URL which contains the API key
     #[error("HTTP request failed: {0}")]
-    HttpError(#[from] reqwest::Error),
+    HttpError(reqwest::Error),
 
     #[error("Message is empty")]
     EmptyMessage,
@@ -23,18 +29,50 @@ pub enum FreeMobileError {
     #[error("Invalid message format: {0}")]
     InvalidMessage(String),
 
+    #[error("Message needs {count} parts, more than the maximum of {max}")]
+    TooManyChunks { count: usize, max: usize },
+
     #[error("Configuration error: {0}")]
     ConfigError(String),
 
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
+    #[error("{source} (gave up after {attempts} attempts)")]
+    RetriesExhausted {
+        attempts: u32,
+        #[source]
+        source: Box<FreeMobileError>,
+    },
+
+    #[error("{source} ({} of {} chunks sent)", .report.sent_count(), .report.chunks.len())]
+    PartialSend {
+        report: Box<SendReport>,
+        #[source]
+        source: Box<FreeMobileError>,
+    },
+
     #[error("Unknown error occurred")]
     Unknown,
 }
 
+impl From<reqwest::Error> for FreeMobileError {
+    fn from(error: reqwest::Error) -> Self {
+        Self::HttpError(error.without_url())
+    }
+}
+
 impl FreeMobileError {
+    /// Map an HTTP error status to its error, without response body
     pub fn from_status_code(status: u16) -> Self {
+        Self::from_response(status, String::new())
+    }
+
+    /// Map an HTTP error status to its error
+    ///
+    /// Statuses documented by FreeMobile get their own variant, any other
+    /// status is kept with its response body in `UnexpectedStatus`.
+    pub fn from_response(status: u16, body: String) -> Self {
         use crate::constants::status_codes::*;
 
         match status {
@@ -42,7 +80,146 @@ impl FreeMobileError {
             TOO_MANY_REQUESTS => Self::TooManyRequests,
             ACCESS_DENIED => Self::AccessDenied,
             SERVER_ERROR => Self::ServerError,
-            _ => Self::Unknown,
+            _ => Self::UnexpectedStatus { status, body },
+        }
+    }
+
+    /// HTTP status code that caused the error, if any
+    pub fn status(&self) -> Option<u16> {
+        use crate::constants::status_codes::*;
+
+        match self {
+            Self::InvalidCredentials => Some(INVALID_CREDENTIALS),
+            Self::TooManyRequests => Some(TOO_MANY_REQUESTS),
+            Self::AccessDenied => Some(ACCESS_DENIED),
+            Self::ServerError => Some(SERVER_ERROR),
+            Self::UnexpectedStatus { status, .. } => Some(*status),
+            Self::HttpError(e) => e.status().map(|status| status.as_u16()),
+            Self::RetriesExhausted { source, .. } | Self::PartialSend { source, .. } => {
+                source.status()
+            }
+            _ => None,
+        }
+    }
+
+    /// Check if the error is transient and sending again later may succeed
+    ///
+    /// Server errors, rate limiting, gateway errors and network timeouts or
+    /// connection failures are retryable.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::ServerError | Self::TooManyRequests => true,
+            Self::UnexpectedStatus { status, .. } => {
+                matches!(status, 408 | 429) || (500..600).contains(status)
+            }
+            Self::HttpError(e) => e.is_timeout() || e.is_connect(),
+            Self::RetriesExhausted { source, .. } | Self::PartialSend { source, .. } => {
+                source.is_retryable()
+            }
+            _ => false,
+        }
+    }
+
+    /// Check if the error comes from rejected credentials or subscription
+    pub fn is_auth_error(&self) -> bool {
+        match self {
+            Self::InvalidCredentials | Self::AccessDenied => true,
+            Self::RetriesExhausted { source, .. } | Self::PartialSend { source, .. } => {
+                source.is_auth_error()
+            }
+            _ => false,
         }
     }
 }
+
+/// Format a response body for error messages
+fn body_excerpt(body: &str) -> String {
+    let body = body.trim();
+    if body.is_empty() {
+        String::new()
+    } else {
+        format!(": {}", body)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_status_code() {
+        assert!(matches!(
+            FreeMobileError::from_status_code(400),
+            FreeMobileError::InvalidCredentials
+        ));
+        assert!(matches!(
+            FreeMobileError::from_status_code(402),
+            FreeMobileError::TooManyRequests
+        ));
+        assert!(matches!(
+            FreeMobileError::from_status_code(403),
+            FreeMobileError::AccessDenied
+        ));
+        assert!(matches!(
+            FreeMobileError::from_status_code(500),
+            FreeMobileError::ServerError
+        ));
+    }
+
+    #[test]
+    fn test_unexpected_status_keeps_body() {
+        let error = FreeMobileError::from_response(502, "Bad Gateway\n".to_string());
+
+        match &error {
+            FreeMobileError::UnexpectedStatus { status, body } => {
+                assert_eq!(*status, 502);
+                assert_eq!(body, "Bad Gateway\n");
+            }
+            other => panic!("Expected UnexpectedStatus, got {:?}", other),
+        }
+        assert_eq!(error.to_string(), "Unexpected HTTP status 502: Bad Gateway");
+        assert_eq!(
+            FreeMobileError::from_status_code(418).to_string(),
+            "Unexpected HTTP status 418"
+        );
+    }
+
+    #[test]
+    fn test_status() {
+        assert_eq!(FreeMobileError::InvalidCredentials.status(), Some(400));
+        assert_eq!(FreeMobileError::from_status_code(503).status(), Some(503));
+        assert_eq!(FreeMobileError::EmptyMessage.status(), None);
+
+        let wrapped = FreeMobileError::RetriesExhausted {
+            attempts: 3,
+            source: Box::new(FreeMobileError::ServerError),
+        };
+        assert_eq!(wrapped.status(), Some(500));
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(FreeMobileError::ServerError.is_retryable());
+        assert!(FreeMobileError::TooManyRequests.is_retryable());
+        assert!(FreeMobileError::from_status_code(502).is_retryable());
+        assert!(FreeMobileError::from_status_code(503).is_retryable());
+        assert!(FreeMobileError::from_status_code(429).is_retryable());
+        assert!(!FreeMobileError::from_status_code(404).is_retryable());
+        assert!(!FreeMobileError::InvalidCredentials.is_retryable());
+        assert!(!FreeMobileError::EmptyMessage.is_retryable());
+    }
+
+    #[test]
+    fn test_is_auth_error() {
+        assert!(FreeMobileError::InvalidCredentials.is_auth_error());
+        assert!(FreeMobileError::AccessDenied.is_auth_error());
+        assert!(!FreeMobileError::ServerError.is_auth_error());
+        assert!(!FreeMobileError::from_status_code(401).is_auth_error());
+
+        let wrapped = FreeMobileError::PartialSend {
+            report: Box::default(),
+            source: Box::new(FreeMobileError::AccessDenied),
+        };
+        assert!(wrapped.is_auth_error());
+    }
+}
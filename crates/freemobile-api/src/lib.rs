@@ -7,12 +7,20 @@
 //! ## Features
 //!
 //! - **FreeMobile SMS API integration** with proper error handling
+//! - **Automatic retries** with exponential backoff for transient failures
+//! - **Client-side rate limiting** shared by all sends, optionally across processes
 //! - **Smart emoji sanitization** supporting 146+ FreeMobile-compatible emojis  
+//! - **Text cleanup** of ANSI escapes, control characters, orphaned joiners and bidi overrides
 //! - **Automatic message chunking** for messages exceeding 999 characters
+//! - **Dry-run planning** showing the exact chunks of a message before sending it
+//! - **GSM-7/UCS-2 encoding analysis** counting the SMS segments of each chunk,
+//!   with optional transliteration to GSM-7
 //! - **Configurable constants** externalized in `constants` module
 //! - **Word-boundary-aware splitting** to avoid breaking words mid-sentence
+//! - **Sentence, paragraph and line splitting strategies** to keep related text together
 //! - **Unicode-aware processing** with proper grapheme cluster handling
 //! - **Async/await support** built on Tokio
+//! - **Pluggable transport** with an in-memory recorder behind the `testing` feature
 //!
 //! ## Quick Start
 //!
@@ -43,18 +51,36 @@
 //!
 //! // Message limits
 //! println!("Max message length: {}", MAX_MESSAGE_LENGTH); // 999
-//! println!("Prefix reserve: {}", PREFIX_RESERVE_LENGTH);   // 8
+//! println!("Part marker: {}", DEFAULT_CHUNK_TEMPLATE);     // [{i}/{n}]
 //!
 //! // Network timeouts
 //! println!("Request timeout: {}s", REQUEST_TIMEOUT_SECS); // 30
 //! println!("Chunk delay: {}ms", CHUNK_DELAY_MS);          // 500
 //! ```
 //!
+//! Network settings can be overridden per client with [`FreeMobileClientBuilder`]:
+//!
+//! ```rust
+//! use freemobile_api::{Credentials, FreeMobileClient};
+//! use std::time::Duration;
+//!
+//! # fn main() -> Result<(), freemobile_api::FreeMobileError> {
+//! # let credentials = Credentials::new("12345678".to_string(), "key".to_string());
+//! let client = FreeMobileClient::builder(credentials)
+//!     .base_url("http://localhost:8080/sendmsg")
+//!     .timeout(Duration::from_secs(10))
+//!     .chunk_delay(Duration::from_millis(100))
+//!     .build()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
 //! ## Message Processing
 //!
 //! The library automatically handles:
 //!
 //! - **Emoji sanitization**: Preserves 146+ supported emojis (✅ ⚡ ❌ ⭐), replaces others with []
+//! - **Text cleanup**: Strips ANSI escapes, control characters and bidi overrides
 //! - **Smart chunking**: Word-boundary-aware splitting with [1/2], [2/2] prefixes
 //! - **Unicode processing**: Proper grapheme cluster handling for international text
 //!
@@ -70,7 +96,7 @@
 //! # let credentials = Credentials::new("12345678".to_string(), "key".to_string());
 //! # let client = FreeMobileClient::new(credentials)?;
 //! match client.send("message").await {
-//!     Ok(()) => println!("Message sent successfully"),
+//!     Ok(_) => println!("Message sent successfully"),
 //!     Err(FreeMobileError::InvalidCredentials) => println!("Check your API credentials"),
 //!     Err(FreeMobileError::TooManyRequests) => println!("Rate limit exceeded"),
 //!     Err(e) => println!("Other error: {}", e),
@@ -82,11 +108,36 @@
 pub mod chunker;
 pub mod client;
 pub mod constants;
+pub mod emoji_table;
+pub mod encoding;
 pub mod error;
+mod macros;
+pub mod plan;
+pub mod rate_limit;
+pub mod report;
+pub mod retry;
 pub mod sanitizer;
+pub mod secret;
 pub mod supported_emojis;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod transport;
 
-pub use chunker::MessageChunker;
-pub use client::{Credentials, FreeMobileClient};
+pub use chunker::{
+    Chunk, ChunkFormat, ChunkIter, ChunkOptions, MarkerPlacement, MessageChunker, Overflow,
+    SplitStrategy,
+};
+pub use client::{Credentials, ExposedCredentials, FreeMobileClient, FreeMobileClientBuilder};
+pub use emoji_table::EmojiTable;
+pub use encoding::{Encoding, EncodingReport};
 pub use error::FreeMobileError;
-pub use sanitizer::MessageSanitizer;
+pub use plan::SendPlan;
+pub use rate_limit::RateLimiter;
+pub use report::{ChunkOutcome, ChunkReport, SendReport};
+pub use retry::RetryPolicy;
+pub use sanitizer::{
+    ControlChars, MessageSanitizer, Replacement, ReplacementKind, SanitizeOptions, SanitizePolicy,
+    SanitizeReport,
+};
+pub use secret::ApiKey;
+pub use transport::{ReqwestTransport, SmsTransport};
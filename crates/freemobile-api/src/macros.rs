@@ -0,0 +1,62 @@
+//! Macros shared by the library and the CLI
+
+/// Implements `NAMES`, `FromStr` and `Display` for an enum from the name of
+/// each variant
+///
+/// Variants holding a value are parsed from their name with the given value,
+/// and displayed with their name whatever their value.
+///
+/// ```
+/// #[derive(Debug, PartialEq)]
+/// enum Mode {
+///     Fast,
+///     Custom(u32),
+/// }
+///
+/// freemobile_api::named_enum!(Mode, "mode", {
+///     Fast => "fast",
+///     Custom(10) => "custom",
+/// });
+///
+/// assert_eq!(Mode::NAMES, ["fast", "custom"]);
+/// assert_eq!("custom".parse::<Mode>().unwrap(), Mode::Custom(10));
+/// assert_eq!(Mode::Custom(3).to_string(), "custom");
+/// assert_eq!(
+///     "slow".parse::<Mode>().unwrap_err().to_string(),
+///     "Configuration error: Unknown mode 'slow', expected one of: fast, custom"
+/// );
+/// ```
+#[doc(hidden)]
+#[macro_export]
+macro_rules! named_enum {
+    ($enum:ident, $kind:literal, { $($variant:ident $(($value:expr))? => $name:literal),+ $(,)? }) => {
+        impl $enum {
+            /// Names accepted by `from_str`
+            pub const NAMES: [&'static str; [$($name),+].len()] = [$($name),+];
+        }
+
+        impl ::std::str::FromStr for $enum {
+            type Err = $crate::FreeMobileError;
+
+            fn from_str(name: &str) -> ::std::result::Result<Self, Self::Err> {
+                match name {
+                    $($name => Ok(Self::$variant $(($value))?),)+
+                    _ => Err($crate::FreeMobileError::ConfigError(format!(
+                        concat!("Unknown ", $kind, " '{}', expected one of: {}"),
+                        name,
+                        Self::NAMES.join(", ")
+                    ))),
+                }
+            }
+        }
+
+        impl ::std::fmt::Display for $enum {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+                let name = match self {
+                    $(Self::$variant { .. } => $name,)+
+                };
+                f.write_str(name)
+            }
+        }
+    };
+}
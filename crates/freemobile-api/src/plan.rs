@@ -0,0 +1,235 @@
+use crate::chunker::{ChunkOptions, MessageChunker};
+use crate::encoding::EncodingReport;
+use crate::error::FreeMobileError;
+use crate::report::SendReport;
+use crate::sanitizer::{MessageSanitizer, Replacement, SanitizeReport};
+use serde::{Deserialize, Serialize};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Formatted chunks of a message, ready to be sent
+///
+/// A plan fixes the chunk boundaries and their `[n/N]` numbering, so a message
+/// interrupted halfway can be resumed with
+/// [`FreeMobileClient::resume`](crate::FreeMobileClient::resume) without
+/// renumbering or re-sending the chunks already delivered. It also describes
+/// exactly what would be sent, for previews and dry runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SendPlan {
+    /// Message after sanitization, before chunking
+    #[serde(default)]
+    pub sanitized: String,
+    /// Formatted chunks, exactly as sent to the API
+    pub chunks: Vec<String>,
+    /// Emojis replaced by the sanitizer
+    #[serde(default)]
+    pub replacements: Vec<Replacement>,
+}
+
+impl SendPlan {
+    /// Chunk and format a sanitized message
+    pub fn new(sanitized_message: &str) -> Self {
+        let chunks = MessageChunker::chunk(sanitized_message);
+        Self {
+            sanitized: sanitized_message.to_string(),
+            chunks: MessageChunker::format_chunks(&chunks),
+            replacements: Vec::new(),
+        }
+    }
+
+    /// Chunk and format a sanitized message with custom chunking settings
+    ///
+    /// # Errors
+    ///
+    /// Returns `FreeMobileError::TooManyChunks` if the message needs more chunks
+    /// than `options.max_chunks` and the overflow strategy is `Overflow::Refuse`.
+    pub fn with_options(
+        sanitized_message: &str,
+        options: &ChunkOptions,
+    ) -> Result<Self, FreeMobileError> {
+        let chunks = MessageChunker::chunk_with_options(sanitized_message, options)?;
+        Ok(Self {
+            sanitized: sanitized_message.to_string(),
+            chunks: MessageChunker::format_chunks_with(&chunks, &options.format),
+            replacements: Vec::new(),
+        })
+    }
+
+    /// Sanitize, chunk and format a raw message
+    pub fn for_message(message: &str) -> Self {
+        let report = MessageSanitizer::sanitize_with_report(message);
+        Self {
+            replacements: report.replacements,
+            ..Self::new(&report.sanitized)
+        }
+    }
+
+    /// Sanitize, chunk and format a raw message with custom chunking settings
+    ///
+    /// # Errors
+    ///
+    /// Same as [`SendPlan::with_options`].
+    pub fn for_message_with(
+        message: &str,
+        options: &ChunkOptions,
+    ) -> Result<Self, FreeMobileError> {
+        Self::from_sanitized(MessageSanitizer::sanitize_with_report(message), options)
+    }
+
+    /// Chunk and format the output of the sanitizer with custom chunking settings
+    ///
+    /// # Errors
+    ///
+    /// Same as [`SendPlan::with_options`].
+    pub fn from_sanitized(
+        report: SanitizeReport,
+        options: &ChunkOptions,
+    ) -> Result<Self, FreeMobileError> {
+        Ok(Self {
+            replacements: report.replacements,
+            ..Self::with_options(&report.sanitized, options)?
+        })
+    }
+
+    /// Rebuild the plan of a send from its report
+    ///
+    /// Only the chunks are known from a report: the sanitized message and the
+    /// replacements are left empty.
+    pub fn from_report(report: &SendReport) -> Self {
+        Self {
+            sanitized: String::new(),
+            chunks: report
+                .chunks
+                .iter()
+                .map(|chunk| chunk.text.clone())
+                .collect(),
+            replacements: Vec::new(),
+        }
+    }
+
+    /// Number of graphemes of each formatted chunk, as counted against `MAX_MESSAGE_LENGTH`
+    pub fn grapheme_counts(&self) -> Vec<usize> {
+        self.chunks
+            .iter()
+            .map(|chunk| chunk.graphemes(true).count())
+            .collect()
+    }
+
+    /// Encoding of each formatted chunk, as delivered by the operator
+    pub fn encodings(&self) -> Vec<EncodingReport> {
+        self.chunks
+            .iter()
+            .map(|chunk| EncodingReport::analyze(chunk))
+            .collect()
+    }
+
+    /// Number of chunks in the plan
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Check if the plan has no chunk to send
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::ChunkReport;
+
+    #[test]
+    fn test_plan_single_chunk() {
+        let plan = SendPlan::new("Hello world");
+        assert_eq!(plan.chunks, vec!["Hello world"]);
+        assert_eq!(plan.len(), 1);
+    }
+
+    #[test]
+    fn test_plan_multiple_chunks() {
+        let plan = SendPlan::new(&"lorem ipsum ".repeat(200));
+        assert_eq!(plan.len(), 3);
+        assert!(plan.chunks[0].starts_with("[1/3] "));
+        assert!(plan.chunks[2].starts_with("[3/3] "));
+    }
+
+    #[test]
+    fn test_plan_for_message() {
+        let plan = SendPlan::for_message("Deploy 🚀 done ✅");
+
+        assert_eq!(plan.sanitized, "Deploy [] done ✅");
+        assert_eq!(plan.chunks, vec!["Deploy [] done ✅"]);
+        assert_eq!(plan.replacements.len(), 1);
+        assert_eq!(plan.replacements[0].original, "🚀");
+        assert_eq!(plan.grapheme_counts(), vec![16]);
+    }
+
+    #[test]
+    fn test_plan_grapheme_counts_fit_limit() {
+        let plan = SendPlan::new(&"é ".repeat(800));
+
+        assert_eq!(plan.len(), 2);
+        assert!(
+            plan.grapheme_counts()
+                .iter()
+                .all(|&count| count <= crate::constants::MAX_MESSAGE_LENGTH)
+        );
+    }
+
+    #[test]
+    fn test_plan_encodings() {
+        let plan = SendPlan::new(&format!("{} ✅", "lorem ipsum ".repeat(100)));
+        let encodings = plan.encodings();
+
+        assert_eq!(encodings.len(), 2);
+        // The brackets of the part markers are in the GSM-7 extension table
+        assert_eq!(encodings[0].encoding, crate::Encoding::Gsm7Extended);
+        assert_eq!(encodings[0].units, plan.grapheme_counts()[0] + 2);
+        assert_eq!(encodings[1].encoding, crate::Encoding::Ucs2);
+        assert_eq!(encodings[1].ucs2_chars, vec!["✅"]);
+    }
+
+    #[test]
+    fn test_plan_with_max_chunks() {
+        let options = ChunkOptions {
+            max_chunks: Some(2),
+            overflow: crate::chunker::Overflow::KeepLast,
+            ..ChunkOptions::default()
+        };
+        let plan = SendPlan::for_message_with(&"lorem ipsum 😀 ".repeat(300), &options).unwrap();
+
+        assert_eq!(plan.len(), 2);
+        assert!(plan.chunks[0].starts_with("[1/2] "));
+        assert_eq!(plan.replacements.len(), 300);
+
+        let refused = SendPlan::for_message_with(
+            &"lorem ipsum ".repeat(300),
+            &ChunkOptions {
+                max_chunks: Some(2),
+                ..ChunkOptions::default()
+            },
+        );
+        assert!(matches!(
+            refused,
+            Err(FreeMobileError::TooManyChunks { max: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_plan_empty_message() {
+        assert!(SendPlan::new("   ").is_empty());
+    }
+
+    #[test]
+    fn test_plan_from_report() {
+        let report = SendReport {
+            chunks: vec![
+                ChunkReport::not_sent(0, "[1/2] first".to_string()),
+                ChunkReport::not_sent(1, "[2/2] second".to_string()),
+            ],
+        };
+
+        let plan = SendPlan::from_report(&report);
+        assert_eq!(plan.chunks, vec!["[1/2] first", "[2/2] second"]);
+    }
+}
@@ -0,0 +1,375 @@
+//! Client-side rate limiting of outgoing requests
+//!
+//! [`RateLimiter`] is a token bucket: up to `burst` requests can be sent at once,
+//! then one request is allowed every `refill_interval`. Requests beyond the budget
+//! reserve a future slot and wait for it, so concurrent sends are serialized in
+//! the order they asked for a token.
+//!
+//! With the `file-lock` feature, the bucket can be stored in a locked file to
+//! share the same budget between several processes on one host. When the file
+//! cannot be used, the limiter falls back to an in-memory bucket.
+
+use crate::constants::rate_limit::{BURST, REFILL_INTERVAL_MS};
+use crate::error::FreeMobileError;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "file-lock")]
+use std::path::{Path, PathBuf};
+
+/// Token bucket state, with timestamps in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Bucket {
+    /// Available tokens, negative when future slots have been reserved
+    tokens: f64,
+    updated_ms: u64,
+}
+
+impl Bucket {
+    fn full(burst: u32, now_ms: u64) -> Self {
+        Self {
+            tokens: f64::from(burst),
+            updated_ms: now_ms,
+        }
+    }
+
+    /// Take a token at `now_ms`, returning how long to wait before using it
+    fn reserve(&mut self, now_ms: u64, burst: u32, refill_interval: Duration) -> Duration {
+        let refill_ms = refill_interval.as_millis().max(1) as f64;
+        let elapsed_ms = now_ms.saturating_sub(self.updated_ms) as f64;
+
+        self.tokens = (self.tokens + elapsed_ms / refill_ms).min(f64::from(burst));
+        self.updated_ms = self.updated_ms.max(now_ms);
+        self.tokens -= 1.0;
+
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_millis((-self.tokens * refill_ms).ceil() as u64)
+        }
+    }
+}
+
+/// Bucket of a single process
+#[derive(Debug)]
+struct MemoryBucket {
+    origin: Instant,
+    bucket: Mutex<Bucket>,
+}
+
+impl MemoryBucket {
+    fn new(burst: u32) -> Self {
+        Self {
+            origin: Instant::now(),
+            bucket: Mutex::new(Bucket::full(burst, 0)),
+        }
+    }
+
+    fn reserve(&self, burst: u32, refill_interval: Duration) -> Duration {
+        let now_ms = self.origin.elapsed().as_millis() as u64;
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        bucket.reserve(now_ms, burst, refill_interval)
+    }
+}
+
+#[derive(Debug)]
+enum Backend {
+    Memory(MemoryBucket),
+    /// Bucket stored in a file, with an in-memory fallback used on IO errors
+    #[cfg(feature = "file-lock")]
+    File {
+        path: PathBuf,
+        fallback: MemoryBucket,
+    },
+}
+
+#[derive(Debug)]
+struct Inner {
+    burst: u32,
+    refill_interval: Duration,
+    backend: Backend,
+}
+
+/// Token bucket rate limiter shared by all the sends of a client
+///
+/// Clones share the same budget, so a limiter can also be shared between
+/// several clients.
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::{Credentials, FreeMobileClient, RateLimiter};
+/// use std::time::Duration;
+///
+/// let credentials = Credentials::new("12345678".to_string(), "your-api-key".to_string());
+/// let client = FreeMobileClient::builder(credentials)
+///     .rate_limiter(RateLimiter::new(3, Duration::from_secs(2)))
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    inner: Arc<Inner>,
+}
+
+impl Default for RateLimiter {
+    fn default() -> Self {
+        Self::new(BURST, Duration::from_millis(REFILL_INTERVAL_MS))
+    }
+}
+
+impl RateLimiter {
+    /// Create an in-memory limiter allowing `burst` requests at once, then one
+    /// request every `refill_interval`
+    pub fn new(burst: u32, refill_interval: Duration) -> Self {
+        let burst = burst.max(1);
+        Self::with_backend(
+            burst,
+            refill_interval,
+            Backend::Memory(MemoryBucket::new(burst)),
+        )
+    }
+
+    /// Create a limiter that never waits
+    pub fn unlimited() -> Self {
+        Self::new(u32::MAX, Duration::ZERO)
+    }
+
+    /// Create a limiter storing its bucket in a locked file
+    ///
+    /// All the processes using the same file share the same budget. The file
+    /// is created if needed, readable and writable by its owner only, and can
+    /// be safely removed when no process uses it. Symbolic links are not
+    /// followed on Unix.
+    ///
+    /// If the file cannot be opened, locked or written, for instance because
+    /// another user owns it, the limiter falls back to an in-memory bucket.
+    #[cfg(feature = "file-lock")]
+    pub fn file_backed(path: impl AsRef<Path>, burst: u32, refill_interval: Duration) -> Self {
+        let burst = burst.max(1);
+        Self::with_backend(
+            burst,
+            refill_interval,
+            Backend::File {
+                path: path.as_ref().to_path_buf(),
+                fallback: MemoryBucket::new(burst),
+            },
+        )
+    }
+
+    fn with_backend(burst: u32, refill_interval: Duration, backend: Backend) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                burst,
+                refill_interval,
+                backend,
+            }),
+        }
+    }
+
+    /// Wait until a request is allowed
+    pub async fn acquire(&self) -> Result<(), FreeMobileError> {
+        let wait = self.reserve().await?;
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+        Ok(())
+    }
+
+    async fn reserve(&self) -> Result<Duration, FreeMobileError> {
+        let inner = &self.inner;
+        match &inner.backend {
+            Backend::Memory(bucket) => Ok(bucket.reserve(inner.burst, inner.refill_interval)),
+            #[cfg(feature = "file-lock")]
+            Backend::File { path, fallback } => {
+                let path = path.clone();
+                let (burst, refill_interval) = (inner.burst, inner.refill_interval);
+                let wait = tokio::task::spawn_blocking(move || {
+                    file_backend::reserve(&path, burst, refill_interval)
+                })
+                .await;
+
+                match wait {
+                    Ok(Ok(wait)) => Ok(wait),
+                    // The shared budget is unavailable, this process keeps its own
+                    _ => Ok(fallback.reserve(burst, refill_interval)),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "file-lock")]
+mod file_backend {
+    use super::Bucket;
+    use fs4::FileExt;
+    use std::fs::{File, OpenOptions};
+    use std::io::{self, Read, Seek, SeekFrom, Write};
+    use std::path::Path;
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    /// Reserve a token from the bucket stored in `path`, holding an exclusive lock
+    pub(super) fn reserve(
+        path: &Path,
+        burst: u32,
+        refill_interval: Duration,
+    ) -> io::Result<Duration> {
+        let mut options = OpenOptions::new();
+        options.read(true).write(true).create(true).truncate(false);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600).custom_flags(libc::O_NOFOLLOW);
+        }
+        let mut file = options.open(path)?;
+        FileExt::lock(&file)?;
+
+        let result = reserve_locked(&mut file, burst, refill_interval);
+        FileExt::unlock(&file)?;
+        result
+    }
+
+    fn reserve_locked(
+        file: &mut File,
+        burst: u32,
+        refill_interval: Duration,
+    ) -> io::Result<Duration> {
+        let now_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+
+        let mut content = String::new();
+        file.read_to_string(&mut content)?;
+        // A missing or corrupted state starts with a full bucket
+        let mut bucket = parse(&content).unwrap_or_else(|| Bucket::full(burst, now_ms));
+
+        let wait = bucket.reserve(now_ms, burst, refill_interval);
+
+        file.set_len(0)?;
+        file.seek(SeekFrom::Start(0))?;
+        write!(file, "{} {}", bucket.tokens, bucket.updated_ms)?;
+        file.sync_data()?;
+
+        Ok(wait)
+    }
+
+    fn parse(content: &str) -> Option<Bucket> {
+        let mut fields = content.split_whitespace();
+        let tokens = fields.next()?.parse().ok()?;
+        let updated_ms = fields.next()?.parse().ok()?;
+        Some(Bucket { tokens, updated_ms })
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+        use tempfile::NamedTempFile;
+
+        #[test]
+        fn test_file_bucket_shared_between_calls() {
+            let file = NamedTempFile::new().unwrap();
+            let interval = Duration::from_secs(60);
+
+            assert_eq!(reserve(file.path(), 2, interval).unwrap(), Duration::ZERO);
+            assert_eq!(reserve(file.path(), 2, interval).unwrap(), Duration::ZERO);
+            let wait = reserve(file.path(), 2, interval).unwrap();
+            assert!(wait > Duration::from_secs(59) && wait <= interval);
+        }
+
+        #[cfg(unix)]
+        #[test]
+        fn test_private_file_and_symlink_refused() {
+            use std::os::unix::fs::PermissionsExt;
+
+            let dir = tempfile::TempDir::new().unwrap();
+            let path = dir.path().join("bucket");
+            reserve(&path, 1, Duration::from_secs(60)).unwrap();
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+
+            let target = dir.path().join("target");
+            std::fs::write(&target, "precious").unwrap();
+            let link = dir.path().join("link");
+            std::os::unix::fs::symlink(&target, &link).unwrap();
+            assert!(reserve(&link, 1, Duration::from_secs(60)).is_err());
+            assert_eq!(std::fs::read_to_string(&target).unwrap(), "precious");
+        }
+
+        #[test]
+        fn test_corrupted_file_starts_full() {
+            let file = NamedTempFile::new().unwrap();
+            std::fs::write(file.path(), "garbage").unwrap();
+
+            let wait = reserve(file.path(), 1, Duration::from_secs(60)).unwrap();
+            assert_eq!(wait, Duration::ZERO);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    #[test]
+    fn test_bucket_burst() {
+        let mut bucket = Bucket::full(3, 0);
+
+        assert_eq!(bucket.reserve(0, 3, SECOND), Duration::ZERO);
+        assert_eq!(bucket.reserve(0, 3, SECOND), Duration::ZERO);
+        assert_eq!(bucket.reserve(0, 3, SECOND), Duration::ZERO);
+        assert_eq!(bucket.reserve(0, 3, SECOND), SECOND);
+    }
+
+    #[test]
+    fn test_bucket_reservations_queue_up() {
+        let mut bucket = Bucket::full(1, 0);
+
+        assert_eq!(bucket.reserve(0, 1, SECOND), Duration::ZERO);
+        assert_eq!(bucket.reserve(0, 1, SECOND), SECOND);
+        assert_eq!(bucket.reserve(0, 1, SECOND), 2 * SECOND);
+        assert_eq!(bucket.reserve(500, 1, SECOND), Duration::from_millis(2500));
+    }
+
+    #[test]
+    fn test_bucket_refill_capped_at_burst() {
+        let mut bucket = Bucket::full(2, 0);
+        bucket.reserve(0, 2, SECOND);
+        bucket.reserve(0, 2, SECOND);
+
+        // A long idle period only refills up to the burst size
+        assert_eq!(bucket.reserve(60_000, 2, SECOND), Duration::ZERO);
+        assert_eq!(bucket.reserve(60_000, 2, SECOND), Duration::ZERO);
+        assert_eq!(bucket.reserve(60_000, 2, SECOND), SECOND);
+    }
+
+    #[tokio::test]
+    async fn test_limiter_clones_share_budget() {
+        let limiter = RateLimiter::new(1, Duration::from_secs(60));
+        let clone = limiter.clone();
+
+        assert_eq!(limiter.reserve().await.unwrap(), Duration::ZERO);
+        assert!(clone.reserve().await.unwrap() > Duration::from_secs(59));
+    }
+
+    #[tokio::test]
+    async fn test_unlimited_limiter() {
+        let limiter = RateLimiter::unlimited();
+        for _ in 0..100 {
+            assert_eq!(limiter.reserve().await.unwrap(), Duration::ZERO);
+        }
+    }
+
+    #[cfg(feature = "file-lock")]
+    #[tokio::test]
+    async fn test_file_limiter_falls_back_to_memory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("missing-dir").join("bucket");
+        let limiter = RateLimiter::file_backed(path, 1, Duration::from_secs(60));
+
+        assert_eq!(limiter.reserve().await.unwrap(), Duration::ZERO);
+        assert!(limiter.reserve().await.unwrap() > Duration::from_secs(59));
+    }
+}
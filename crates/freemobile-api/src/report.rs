@@ -0,0 +1,141 @@
+use std::time::Duration;
+
+/// Outcome of a single chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkOutcome {
+    /// The chunk was accepted by the API
+    Sent,
+    /// The chunk was attempted but failed, with the error message
+    Failed(String),
+    /// The chunk was not attempted because a previous chunk failed
+    NotSent,
+}
+
+/// Delivery report of a single formatted chunk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChunkReport {
+    /// Position of the chunk in the message, starting at 0
+    pub index: usize,
+    /// Formatted chunk text, including its `[n/N]` prefix
+    pub text: String,
+    /// HTTP status of the last attempt, if a response was received
+    pub status: Option<u16>,
+    /// Response body of the last attempt, captured when it returned an error status
+    pub body: Option<String>,
+    /// Time spent sending the chunk, including retries
+    pub latency: Duration,
+    /// Number of requests made for this chunk
+    pub attempts: u32,
+    pub outcome: ChunkOutcome,
+}
+
+impl ChunkReport {
+    pub(crate) fn not_sent(index: usize, text: String) -> Self {
+        Self {
+            index,
+            text,
+            status: None,
+            body: None,
+            latency: Duration::ZERO,
+            attempts: 0,
+            outcome: ChunkOutcome::NotSent,
+        }
+    }
+
+    /// Check if the chunk reached the API successfully
+    pub fn is_sent(&self) -> bool {
+        self.outcome == ChunkOutcome::Sent
+    }
+}
+
+/// Delivery report of a message, listing every formatted chunk
+///
+/// Returned by [`FreeMobileClient::send`](crate::FreeMobileClient::send) on success,
+/// and attached to [`FreeMobileError::PartialSend`](crate::FreeMobileError::PartialSend)
+/// when some chunks were sent before a failure.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SendReport {
+    pub chunks: Vec<ChunkReport>,
+}
+
+impl SendReport {
+    /// Check if every chunk was sent
+    pub fn is_complete(&self) -> bool {
+        self.chunks.iter().all(ChunkReport::is_sent)
+    }
+
+    /// Number of chunks sent successfully
+    pub fn sent_count(&self) -> usize {
+        self.chunks.iter().filter(|chunk| chunk.is_sent()).count()
+    }
+
+    /// Index of the first chunk that was not sent, where sending should resume
+    pub fn first_unsent_index(&self) -> Option<usize> {
+        self.chunks
+            .iter()
+            .find(|chunk| !chunk.is_sent())
+            .map(|chunk| chunk.index)
+    }
+
+    /// Total number of requests made, including retries
+    pub fn total_attempts(&self) -> u32 {
+        self.chunks.iter().map(|chunk| chunk.attempts).sum()
+    }
+
+    /// Total time spent sending chunks, excluding delays between chunks
+    pub fn total_latency(&self) -> Duration {
+        self.chunks.iter().map(|chunk| chunk.latency).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sent(index: usize, attempts: u32) -> ChunkReport {
+        ChunkReport {
+            index,
+            text: format!("chunk {}", index),
+            status: Some(200),
+            body: None,
+            latency: Duration::from_millis(100),
+            attempts,
+            outcome: ChunkOutcome::Sent,
+        }
+    }
+
+    #[test]
+    fn test_complete_report() {
+        let report = SendReport {
+            chunks: vec![sent(0, 1), sent(1, 2)],
+        };
+
+        assert!(report.is_complete());
+        assert_eq!(report.sent_count(), 2);
+        assert_eq!(report.first_unsent_index(), None);
+        assert_eq!(report.total_attempts(), 3);
+        assert_eq!(report.total_latency(), Duration::from_millis(200));
+    }
+
+    #[test]
+    fn test_partial_report() {
+        let failed = ChunkReport {
+            status: Some(402),
+            body: Some(String::new()),
+            outcome: ChunkOutcome::Failed("Too many requests".to_string()),
+            ..sent(1, 3)
+        };
+        let report = SendReport {
+            chunks: vec![
+                sent(0, 1),
+                failed,
+                ChunkReport::not_sent(2, "chunk 2".to_string()),
+            ],
+        };
+
+        assert!(!report.is_complete());
+        assert_eq!(report.sent_count(), 1);
+        assert_eq!(report.first_unsent_index(), Some(1));
+        assert_eq!(report.total_attempts(), 4);
+    }
+}
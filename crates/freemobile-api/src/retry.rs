@@ -0,0 +1,162 @@
+use crate::constants::retry::{
+    BASE_DELAY_MS, JITTER_RATIO, MAX_ATTEMPTS, MAX_DELAY_MS, RATE_LIMIT_COOLDOWN_MS,
+};
+use crate::error::FreeMobileError;
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+use std::time::Duration;
+
+/// Retry policy applied to each chunk sent by the client
+///
+/// Failed sends are retried with an exponential backoff: the n-th retry waits
+/// `base_delay * 2^(n-1)`, capped at `max_delay`, minus a random share of up to
+/// `jitter` of that delay. `TooManyRequests` errors wait at least `rate_limit_cooldown`.
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy {
+///     max_attempts: 5,
+///     base_delay: Duration::from_millis(500),
+///     ..RetryPolicy::default()
+/// };
+/// ```
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts per chunk, including the first one
+    pub max_attempts: u32,
+    /// Delay before the first retry
+    pub base_delay: Duration,
+    /// Upper bound of the backoff delay
+    pub max_delay: Duration,
+    /// Share of the delay randomly removed to spread retries (0.0 to 1.0)
+    pub jitter: f64,
+    /// Minimum delay before retrying after a `TooManyRequests` error
+    pub rate_limit_cooldown: Duration,
+    /// Decides which errors are worth retrying (defaults to `FreeMobileError::is_retryable`)
+    pub retryable: fn(&FreeMobileError) -> bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: MAX_ATTEMPTS,
+            base_delay: Duration::from_millis(BASE_DELAY_MS),
+            max_delay: Duration::from_millis(MAX_DELAY_MS),
+            jitter: JITTER_RATIO,
+            rate_limit_cooldown: Duration::from_millis(RATE_LIMIT_COOLDOWN_MS),
+            retryable: FreeMobileError::is_retryable,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Policy sending each chunk only once
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Self::default()
+        }
+    }
+
+    /// Check if a failed attempt should be retried
+    ///
+    /// `attempt` is the number of attempts already made, starting at 1.
+    pub fn should_retry(&self, attempt: u32, error: &FreeMobileError) -> bool {
+        attempt < self.max_attempts && (self.retryable)(error)
+    }
+
+    /// Delay to wait after the given failed attempt, before the next one
+    pub fn delay_for(&self, attempt: u32, error: &FreeMobileError) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let backoff = self
+            .base_delay
+            .saturating_mul(1 << exponent)
+            .min(self.max_delay);
+
+        let jitter = self.jitter.clamp(0.0, 1.0) * random_ratio();
+        let delay = backoff.mul_f64(1.0 - jitter);
+
+        if matches!(error, FreeMobileError::TooManyRequests) {
+            delay.max(self.rate_limit_cooldown)
+        } else {
+            delay
+        }
+    }
+}
+
+/// Random value in [0, 1), good enough to spread retries without an RNG dependency
+fn random_ratio() -> f64 {
+    let random = RandomState::new().hash_one(std::time::SystemTime::now());
+    (random >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy_without_jitter() -> RetryPolicy {
+        RetryPolicy {
+            jitter: 0.0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    #[test]
+    fn test_exponential_backoff() {
+        let policy = policy_without_jitter();
+        let error = FreeMobileError::ServerError;
+
+        assert_eq!(policy.delay_for(1, &error), Duration::from_millis(1000));
+        assert_eq!(policy.delay_for(2, &error), Duration::from_millis(2000));
+        assert_eq!(policy.delay_for(3, &error), Duration::from_millis(4000));
+    }
+
+    #[test]
+    fn test_backoff_capped_at_max_delay() {
+        let policy = policy_without_jitter();
+        let error = FreeMobileError::ServerError;
+
+        assert_eq!(policy.delay_for(10, &error), policy.max_delay);
+        assert_eq!(policy.delay_for(100, &error), policy.max_delay);
+    }
+
+    #[test]
+    fn test_rate_limit_cooldown() {
+        let policy = policy_without_jitter();
+
+        assert_eq!(
+            policy.delay_for(1, &FreeMobileError::TooManyRequests),
+            policy.rate_limit_cooldown
+        );
+    }
+
+    #[test]
+    fn test_jitter_bounds() {
+        let policy = RetryPolicy {
+            jitter: 0.5,
+            ..RetryPolicy::default()
+        };
+
+        for _ in 0..100 {
+            let delay = policy.delay_for(1, &FreeMobileError::ServerError);
+            assert!(delay > Duration::from_millis(500) && delay <= Duration::from_millis(1000));
+        }
+    }
+
+    #[test]
+    fn test_should_retry() {
+        let policy = RetryPolicy::default();
+
+        assert!(policy.should_retry(1, &FreeMobileError::ServerError));
+        assert!(policy.should_retry(2, &FreeMobileError::TooManyRequests));
+        assert!(!policy.should_retry(3, &FreeMobileError::ServerError));
+        assert!(!policy.should_retry(1, &FreeMobileError::InvalidCredentials));
+        assert!(!policy.should_retry(1, &FreeMobileError::AccessDenied));
+        assert!(policy.should_retry(1, &FreeMobileError::from_status_code(503)));
+        assert!(!RetryPolicy::none().should_retry(1, &FreeMobileError::ServerError));
+    }
+}
@@ -1,31 +1,464 @@
-use crate::supported_emojis::is_supported_emoji;
+use crate::constants::EMOJI_PLACEHOLDER;
+use crate::emoji_table::EmojiTable;
+use crate::encoding::to_gsm7;
+use crate::supported_emojis::closest_supported_emoji;
 use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::ops::Range;
 use std::sync::LazyLock;
 
-/// Static regex for emoji detection, compiled once at startup
-static EMOJI_REGEX: LazyLock<Regex> = LazyLock::new(|| {
-    Regex::new(r"[\p{Emoji_Presentation}\p{Extended_Pictographic}][\u{FE0F}\u{20E3}]?")
-        .expect("Invalid emoji regex")
+/// Static regex for the text handled by the sanitizer, compiled once at startup
+///
+/// Alternatives are tried in order, so escape sequences take precedence over
+/// the control characters they start with. An OSC sequence without its BEL or
+/// `ESC \` terminator on the same line only loses its introducer, so a stray
+/// `ESC ]` cannot swallow the rest of the message.
+static TOKEN_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(concat!(
+        r"(?P<ansi>\x1B(?:\[[0-?]*[ -/]*[@-~]|\][^\x07\x1B\n]*(?:\x07|\x1B\\)|[0-~]))",
+        r"|(?P<emoji>[\p{Emoji_Presentation}\p{Extended_Pictographic}][\u{FE0F}\u{20E3}]?)",
+        r"|(?P<joiner>[\u{200D}\u{FE0E}\u{FE0F}])",
+        r"|(?P<bidi>[\u{202A}-\u{202E}\u{2066}-\u{2069}])",
+        r"|(?P<control>[\x00-\x08\x0B-\x1F\x7F\u{80}-\u{9F}])",
+        r"|(?P<text>[^\x00-\x7F]\p{M}*|[\x20-\x7E]\p{M}+|[\t`])",
+    ))
+    .expect("Invalid sanitizer regex")
 });
 
+/// What unsupported emojis are replaced with
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::{MessageSanitizer, SanitizePolicy};
+///
+/// let sanitize = |policy| MessageSanitizer::sanitize_with("🔥 disk full", &policy).sanitized;
+/// assert_eq!(sanitize(SanitizePolicy::default()), "[] disk full");
+/// assert_eq!(sanitize(SanitizePolicy::Placeholder("(?)".to_string())), "(?) disk full");
+/// assert_eq!(sanitize(SanitizePolicy::Drop), " disk full");
+/// assert_eq!(sanitize(SanitizePolicy::Name), "[fire] disk full");
+/// assert_eq!(sanitize(SanitizePolicy::Shortcode), ":fire: disk full");
+/// assert_eq!(sanitize(SanitizePolicy::Closest), "⚠️ disk full");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SanitizePolicy {
+    /// A fixed text, `EMOJI_PLACEHOLDER` by default
+    Placeholder(String),
+    /// Nothing, the emoji is removed
+    Drop,
+    /// The Unicode name of the emoji in brackets, e.g. `[fire]`
+    Name,
+    /// The `:shortcode:` of the emoji, e.g. `:fire:`
+    Shortcode,
+    /// The closest supported emoji, e.g. ⚠️ for 🔥, or `EMOJI_PLACEHOLDER` if there is none
+    Closest,
+}
+
+impl Default for SanitizePolicy {
+    fn default() -> Self {
+        Self::Placeholder(EMOJI_PLACEHOLDER.to_string())
+    }
+}
+
+crate::named_enum!(SanitizePolicy, "emoji policy", {
+    Placeholder(EMOJI_PLACEHOLDER.to_string()) => "placeholder",
+    Drop => "drop",
+    Name => "name",
+    Shortcode => "shortcode",
+    Closest => "closest",
+});
+
+impl SanitizePolicy {
+    /// Text replacing an unsupported emoji
+    ///
+    /// Emojis unknown to the Unicode emoji list get `EMOJI_PLACEHOLDER` with
+    /// the `Name` and `Shortcode` policies.
+    fn replacement(&self, emoji: &str) -> String {
+        let known = || emojis::get(emoji).or_else(|| emojis::get(&emoji.replace('\u{FE0F}', "")));
+
+        match self {
+            Self::Placeholder(placeholder) => placeholder.clone(),
+            Self::Drop => String::new(),
+            Self::Name => known().map_or_else(
+                || EMOJI_PLACEHOLDER.to_string(),
+                |known| format!("[{}]", known.name()),
+            ),
+            Self::Shortcode => known().map_or_else(
+                || EMOJI_PLACEHOLDER.to_string(),
+                |known| {
+                    let shortcode = known.shortcode().map_or_else(
+                        || known.name().replace(|c: char| !c.is_alphanumeric(), "_"),
+                        str::to_string,
+                    );
+                    format!(":{}:", shortcode)
+                },
+            ),
+            Self::Closest => closest_supported_emoji(emoji)
+                .unwrap_or(EMOJI_PLACEHOLDER)
+                .to_string(),
+        }
+    }
+}
+
+/// What C0 and C1 control characters other than `\n` and `\t` become
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::{ControlChars, MessageSanitizer, SanitizeOptions};
+///
+/// let options = SanitizeOptions {
+///     controls: ControlChars::Escape,
+///     ..SanitizeOptions::default()
+/// };
+/// let report = MessageSanitizer::sanitize_with_options("ding\x07", &options, &Default::default());
+/// assert_eq!(report.sanitized, "ding\\x07");
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ControlChars {
+    /// Remove them
+    #[default]
+    Remove,
+    /// Replace them with their `\xNN` escape
+    Escape,
+    /// Send them as is
+    Keep,
+}
+
+crate::named_enum!(ControlChars, "control characters handling", {
+    Remove => "remove",
+    Escape => "escape",
+    Keep => "keep",
+});
+
+impl ControlChars {
+    /// Text replacing a control character, or `None` to keep it
+    fn replacement(self, control: &str) -> Option<String> {
+        match self {
+            Self::Remove => Some(String::new()),
+            Self::Escape => Some(
+                control
+                    .chars()
+                    .map(|c| format!("\\x{:02X}", u32::from(c)))
+                    .collect(),
+            ),
+            Self::Keep => None,
+        }
+    }
+}
+
+/// Sanitizer settings
+///
+/// Besides replacing unsupported emojis, the sanitizer cleans up text that
+/// would reach the phone garbled: every pass is enabled by default, except the
+/// GSM-7 transliteration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SanitizeOptions {
+    /// What unsupported emojis are replaced with
+    pub policy: SanitizePolicy,
+    /// Strip ANSI escape sequences, e.g. the colors of piped tool output
+    pub strip_ansi: bool,
+    /// What control characters other than `\n` and `\t` become
+    pub controls: ControlChars,
+    /// Remove zero-width joiners and variation selectors left without their
+    /// emoji once it is replaced
+    pub collapse_joiners: bool,
+    /// Remove bidirectional embedding, override and isolate characters
+    pub strip_bidi: bool,
+    /// Replace characters missing from the GSM-7 alphabet with their GSM-7
+    /// equivalent when they have one, so messages are not sent in UCS-2
+    /// (disabled by default, see [`to_gsm7`](crate::encoding::to_gsm7))
+    pub transliterate: bool,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self {
+            policy: SanitizePolicy::default(),
+            strip_ansi: true,
+            controls: ControlChars::default(),
+            collapse_joiners: true,
+            strip_bidi: true,
+            transliterate: false,
+        }
+    }
+}
+
+impl SanitizeOptions {
+    /// Only replace unsupported emojis, as `MessageSanitizer::sanitize` did
+    /// before the cleanup passes were added
+    pub fn emojis_only() -> Self {
+        Self {
+            policy: SanitizePolicy::default(),
+            strip_ansi: false,
+            controls: ControlChars::Keep,
+            collapse_joiners: false,
+            strip_bidi: false,
+            transliterate: false,
+        }
+    }
+}
+
+/// What a sanitizer replacement applies to
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReplacementKind {
+    /// Unsupported emoji
+    #[default]
+    Emoji,
+    /// ANSI escape sequence
+    Ansi,
+    /// C0 or C1 control character
+    Control,
+    /// Zero-width joiner or variation selector left without its emoji
+    Joiner,
+    /// Bidirectional formatting character
+    Bidi,
+    /// Character replaced with its GSM-7 equivalent
+    Transliteration,
+}
+
+impl fmt::Display for ReplacementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Emoji => "emoji",
+            Self::Ansi => "ansi",
+            Self::Control => "control",
+            Self::Joiner => "joiner",
+            Self::Bidi => "bidi",
+            Self::Transliteration => "transliteration",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Text replaced or removed during sanitization
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Replacement {
+    /// What the replaced text is, reports predating the other passes only hold emojis
+    #[serde(default)]
+    pub kind: ReplacementKind,
+    /// Text found in the original message
+    pub original: String,
+    /// Unicode code points of `original`, e.g. `[0x1F680]` for 🚀
+    #[serde(default)]
+    pub codepoints: Vec<u32>,
+    /// Text inserted in its place
+    pub replacement: String,
+    /// Byte position of the replaced text in the original message
+    pub byte_offset: usize,
+}
+
+impl Replacement {
+    /// Code points of the replaced text in `U+XXXX` notation, separated by spaces
+    pub fn codepoints_label(&self) -> String {
+        self.codepoints
+            .iter()
+            .map(|codepoint| format!("U+{:04X}", codepoint))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Result of [`MessageSanitizer::sanitize_with_report`]
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::MessageSanitizer;
+///
+/// let report = MessageSanitizer::sanitize_with_report("Deploy 🚀 done ✅");
+/// assert_eq!(report.sanitized, "Deploy [] done ✅");
+/// assert_eq!(report.replacements[0].original, "🚀");
+/// assert_eq!(report.replacements[0].codepoints_label(), "U+1F680");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SanitizeReport {
+    /// Sanitized message
+    pub sanitized: String,
+    /// Replaced text, in the order it appears in the original message
+    pub replacements: Vec<Replacement>,
+}
+
+impl SanitizeReport {
+    /// Check if the sanitizer changed the message
+    pub fn is_modified(&self) -> bool {
+        !self.replacements.is_empty()
+    }
+
+    /// Replacements of a given kind
+    pub fn replacements_of(&self, kind: ReplacementKind) -> impl Iterator<Item = &Replacement> {
+        self.replacements
+            .iter()
+            .filter(move |replacement| replacement.kind == kind)
+    }
+}
+
+/// Text matched by `TOKEN_REGEX`
+struct Token {
+    kind: ReplacementKind,
+    range: Range<usize>,
+}
+
 /// Message sanitizer for FreeMobile API compatibility
 pub struct MessageSanitizer;
 
 impl MessageSanitizer {
     /// Sanitizes a message by preserving supported emojis and replacing unsupported ones with []
     pub fn sanitize(message: &str) -> String {
-        EMOJI_REGEX
-            .replace_all(message, |caps: &regex::Captures| {
-                let emoji = &caps[0];
-                let normalized = emoji.replace('\u{FE0F}', "");
-
-                if is_supported_emoji(&normalized) || is_supported_emoji(emoji) {
-                    emoji.to_string()
-                } else {
-                    "[]".to_string()
+        Self::sanitize_with_report(message).sanitized
+    }
+
+    /// Sanitizes a message, also listing every replaced emoji
+    pub fn sanitize_with_report(message: &str) -> SanitizeReport {
+        Self::sanitize_with(message, &SanitizePolicy::default())
+    }
+
+    /// Sanitizes a message, replacing unsupported emojis according to `policy`
+    pub fn sanitize_with(message: &str, policy: &SanitizePolicy) -> SanitizeReport {
+        Self::sanitize_with_table(message, policy, EmojiTable::builtin_ref())
+    }
+
+    /// Sanitizes a message, keeping the emojis supported by `table`
+    ///
+    /// Unsupported emojis are replaced by their substitution in `table`, or
+    /// else according to `policy`.
+    pub fn sanitize_with_table(
+        message: &str,
+        policy: &SanitizePolicy,
+        table: &EmojiTable,
+    ) -> SanitizeReport {
+        let options = SanitizeOptions {
+            policy: policy.clone(),
+            ..SanitizeOptions::default()
+        };
+        Self::sanitize_with_options(message, &options, table)
+    }
+
+    /// Sanitizes a message with custom settings, keeping the emojis supported by `table`
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use freemobile_api::{EmojiTable, MessageSanitizer, ReplacementKind, SanitizeOptions};
+    ///
+    /// let report = MessageSanitizer::sanitize_with_options(
+    ///     "\x1B[31mFAILED\x1B[0m 👩\u{200D}💻",
+    ///     &SanitizeOptions::default(),
+    ///     &EmojiTable::builtin(),
+    /// );
+    /// assert_eq!(report.sanitized, "FAILED [][]");
+    /// assert_eq!(report.replacements_of(ReplacementKind::Ansi).count(), 2);
+    /// assert_eq!(report.replacements_of(ReplacementKind::Joiner).count(), 1);
+    /// ```
+    pub fn sanitize_with_options(
+        message: &str,
+        options: &SanitizeOptions,
+        table: &EmojiTable,
+    ) -> SanitizeReport {
+        let tokens: Vec<Token> = TOKEN_REGEX
+            .captures_iter(message)
+            .map(|caps| {
+                let (kind, matched) = [
+                    (ReplacementKind::Ansi, "ansi"),
+                    (ReplacementKind::Emoji, "emoji"),
+                    (ReplacementKind::Joiner, "joiner"),
+                    (ReplacementKind::Bidi, "bidi"),
+                    (ReplacementKind::Control, "control"),
+                    (ReplacementKind::Transliteration, "text"),
+                ]
+                .into_iter()
+                .find_map(|(kind, name)| caps.name(name).map(|matched| (kind, matched)))
+                .expect("Match without group");
+
+                match kind {
+                    // Escape sequences left in place still have their control character handled
+                    ReplacementKind::Ansi if !options.strip_ansi => Token {
+                        kind: ReplacementKind::Control,
+                        range: matched.start()..matched.start() + 1,
+                    },
+                    kind => Token {
+                        kind,
+                        range: matched.range(),
+                    },
+                }
+            })
+            .collect();
+
+        let mut changes: Vec<Option<String>> = tokens
+            .iter()
+            .map(|token| {
+                let text = &message[token.range.clone()];
+                match token.kind {
+                    ReplacementKind::Emoji if table.is_supported(text) => None,
+                    ReplacementKind::Emoji => Some(
+                        table
+                            .substitution(text)
+                            .map_or_else(|| options.policy.replacement(text), str::to_string),
+                    ),
+                    ReplacementKind::Ansi => Some(String::new()),
+                    ReplacementKind::Control => options.controls.replacement(text),
+                    ReplacementKind::Bidi if options.strip_bidi => Some(String::new()),
+                    ReplacementKind::Transliteration if options.transliterate => to_gsm7(text),
+                    ReplacementKind::Bidi
+                    | ReplacementKind::Joiner
+                    | ReplacementKind::Transliteration => None,
                 }
             })
-            .to_string()
+            .collect();
+
+        // Joiners are only orphaned once the emojis next to them are replaced
+        if options.collapse_joiners {
+            for index in 0..tokens.len() {
+                let token = &tokens[index];
+                if token.kind != ReplacementKind::Joiner {
+                    continue;
+                }
+                let removed = |other: &Token, changed: bool| {
+                    changed
+                        && matches!(other.kind, ReplacementKind::Emoji | ReplacementKind::Joiner)
+                };
+                let after_removed = index > 0
+                    && tokens[index - 1].range.end == token.range.start
+                    && removed(&tokens[index - 1], changes[index - 1].is_some());
+                // Variation selectors only depend on the character before them
+                let before_removed = &message[token.range.clone()] == "\u{200D}"
+                    && tokens.get(index + 1).is_some_and(|next| {
+                        next.range.start == token.range.end
+                            && removed(next, changes[index + 1].is_some())
+                    });
+                if after_removed || before_removed {
+                    changes[index] = Some(String::new());
+                }
+            }
+        }
+
+        let mut sanitized = String::with_capacity(message.len());
+        let mut replacements = Vec::new();
+        let mut position = 0;
+        for (token, change) in tokens.into_iter().zip(changes) {
+            let Some(replacement) = change else {
+                continue;
+            };
+            let original = &message[token.range.clone()];
+            sanitized.push_str(&message[position..token.range.start]);
+            sanitized.push_str(&replacement);
+            position = token.range.end;
+            replacements.push(Replacement {
+                kind: token.kind,
+                original: original.to_string(),
+                codepoints: original.chars().map(u32::from).collect(),
+                replacement,
+                byte_offset: token.range.start,
+            });
+        }
+        sanitized.push_str(&message[position..]);
+
+        SanitizeReport {
+            sanitized,
+            replacements,
+        }
     }
 }
 
@@ -66,6 +499,282 @@ mod tests {
         assert_eq!(MessageSanitizer::sanitize(input), input);
     }
 
+    #[test]
+    fn test_replacements_listed() {
+        let report = MessageSanitizer::sanitize_with_report("Go 🚀 ✅ now 😀");
+        let replacements = &report.replacements;
+
+        assert!(report.is_modified());
+        assert_eq!(report.sanitized, "Go [] ✅ now []");
+        assert_eq!(replacements.len(), 2);
+        assert_eq!(replacements[0].original, "🚀");
+        assert_eq!(replacements[0].replacement, "[]");
+        assert_eq!(replacements[0].byte_offset, 3);
+        assert_eq!(replacements[1].original, "😀");
+        assert_eq!(replacements[1].byte_offset, "Go 🚀 ✅ now ".len());
+    }
+
+    #[test]
+    fn test_replacement_codepoints() {
+        let report = MessageSanitizer::sanitize_with_report("Flag: 🏳️ keycap: 🔟");
+
+        assert_eq!(report.replacements[0].codepoints, vec![0x1F3F3, 0xFE0F]);
+        assert_eq!(report.replacements[0].codepoints_label(), "U+1F3F3 U+FE0F");
+        assert_eq!(report.replacements[1].codepoints_label(), "U+1F51F");
+        assert!(!MessageSanitizer::sanitize_with_report("Café ✅").is_modified());
+    }
+
+    #[test]
+    fn test_replacement_without_codepoints_deserializes() {
+        let replacement: Replacement =
+            serde_json::from_str(r#"{"original":"🚀","replacement":"[]","byte_offset":3}"#)
+                .unwrap();
+        assert!(replacement.codepoints.is_empty());
+        assert_eq!(replacement.kind, ReplacementKind::Emoji);
+    }
+
+    #[test]
+    fn test_sanitize_policies() {
+        let message = "Launch 🚀 then 🎉, keycap 🔟 and crab 🦀 ✅";
+        let sanitize = |policy| MessageSanitizer::sanitize_with(message, &policy).sanitized;
+
+        assert_eq!(
+            sanitize(SanitizePolicy::Placeholder("?".to_string())),
+            "Launch ? then ?, keycap ? and crab ? ✅"
+        );
+        assert_eq!(
+            sanitize(SanitizePolicy::Drop),
+            "Launch  then , keycap  and crab  ✅"
+        );
+        assert_eq!(
+            sanitize(SanitizePolicy::Name),
+            "Launch [rocket] then [party popper], keycap [keycap: 10] and crab [crab] ✅"
+        );
+        assert_eq!(
+            sanitize(SanitizePolicy::Shortcode),
+            "Launch :rocket: then :tada:, keycap :keycap_ten: and crab :crab: ✅"
+        );
+        assert_eq!(
+            sanitize(SanitizePolicy::Closest),
+            "Launch ✈️ then ✨, keycap [] and crab [] ✅"
+        );
+    }
+
+    #[test]
+    fn test_policy_replacements_reported() {
+        let report = MessageSanitizer::sanitize_with("🔥 disk full", &SanitizePolicy::Closest);
+
+        assert_eq!(report.replacements[0].original, "🔥");
+        assert_eq!(report.replacements[0].replacement, "⚠️");
+        assert_eq!(
+            MessageSanitizer::sanitize_with_report("🔥").replacements[0].replacement,
+            EMOJI_PLACEHOLDER
+        );
+    }
+
+    #[test]
+    fn test_policy_names() {
+        for name in SanitizePolicy::NAMES {
+            assert_eq!(name.parse::<SanitizePolicy>().unwrap().to_string(), name);
+        }
+        assert_eq!(
+            "placeholder".parse::<SanitizePolicy>().unwrap(),
+            SanitizePolicy::default()
+        );
+        assert!("emoji".parse::<SanitizePolicy>().is_err());
+    }
+
+    #[test]
+    fn test_sanitize_with_table() {
+        let mut table = EmojiTable::builtin();
+        table.add_supported("🫠");
+        table.add_substitution("🔥", "(fire)");
+        let report =
+            MessageSanitizer::sanitize_with_table("🫠 🔥 🚀 ✅", &SanitizePolicy::Closest, &table);
+
+        assert_eq!(report.sanitized, "🫠 (fire) ✈️ ✅");
+        assert_eq!(report.replacements.len(), 2);
+        assert_eq!(report.replacements[0].replacement, "(fire)");
+    }
+
+    #[test]
+    fn test_ansi_stripped() {
+        let message = "\x1B[1;31mERROR\x1B[0m disk \x1B]8;;http://x\x07full\x1B]8;;\x07\x1Bc";
+        let report = MessageSanitizer::sanitize_with_report(message);
+
+        assert_eq!(report.sanitized, "ERROR disk full");
+        assert_eq!(report.replacements.len(), 5);
+        assert_eq!(report.replacements[0].kind, ReplacementKind::Ansi);
+        assert_eq!(report.replacements[0].original, "\x1B[1;31m");
+        assert_eq!(report.replacements[1].byte_offset, "\x1B[1;31mERROR".len());
+    }
+
+    #[test]
+    fn test_unterminated_osc() {
+        // Only the introducer of an unterminated OSC is stripped
+        let report = MessageSanitizer::sanitize_with_report("line1\x1B]oops\nline2\nline3");
+        assert_eq!(report.sanitized, "line1oops\nline2\nline3");
+        assert_eq!(report.replacements[0].original, "\x1B]");
+
+        let report = MessageSanitizer::sanitize_with_report("a\x1B]0;title\nb\x07c");
+        assert_eq!(report.sanitized, "a0;title\nbc");
+    }
+
+    #[test]
+    fn test_control_chars() {
+        let message = "a\0b\x07c\r\n\td\u{85}e\x1B[0m";
+        let sanitize = |controls| {
+            let options = SanitizeOptions {
+                controls,
+                strip_ansi: false,
+                ..SanitizeOptions::default()
+            };
+            MessageSanitizer::sanitize_with_options(message, &options, &EmojiTable::builtin())
+        };
+
+        let report = sanitize(ControlChars::Remove);
+        assert_eq!(report.sanitized, "abc\n\tde[0m");
+        assert_eq!(report.replacements_of(ReplacementKind::Control).count(), 5);
+        assert_eq!(
+            sanitize(ControlChars::Escape).sanitized,
+            "a\\x00b\\x07c\\x0D\n\td\\x85e\\x1B[0m"
+        );
+        assert_eq!(sanitize(ControlChars::Keep).sanitized, message);
+        assert!(!sanitize(ControlChars::Keep).is_modified());
+    }
+
+    #[test]
+    fn test_orphaned_joiners_collapsed() {
+        let report = MessageSanitizer::sanitize_with_report("Dev: 👩\u{200D}💻, ✅\u{200D}🔥");
+
+        assert_eq!(report.sanitized, "Dev: [][], ✅[]");
+        let joiners: Vec<_> = report.replacements_of(ReplacementKind::Joiner).collect();
+        assert_eq!(joiners.len(), 2);
+        assert_eq!(joiners[0].codepoints_label(), "U+200D");
+        assert_eq!(joiners[0].byte_offset, "Dev: 👩".len());
+
+        // Text presentation selector of a replaced emoji
+        assert_eq!(MessageSanitizer::sanitize("🚀\u{FE0E} go"), "[] go");
+
+        // Selectors and joiners of kept characters are left alone
+        for message in ["1\u{FE0F}\u{20E3}", "✅\u{FE0E}", "क्\u{200D}ष"] {
+            assert_eq!(MessageSanitizer::sanitize(message), message);
+        }
+
+        let options = SanitizeOptions {
+            collapse_joiners: false,
+            ..SanitizeOptions::default()
+        };
+        let report = MessageSanitizer::sanitize_with_options(
+            "👩\u{200D}💻",
+            &options,
+            &EmojiTable::builtin(),
+        );
+        assert_eq!(report.sanitized, "[]\u{200D}[]");
+    }
+
+    #[test]
+    fn test_bidi_neutralized() {
+        let message = "invoice\u{202E}fdp.exe\u{202C} \u{2067}שלום\u{2069} \u{200F}";
+        let report = MessageSanitizer::sanitize_with_report(message);
+
+        assert_eq!(report.sanitized, "invoicefdp.exe שלום \u{200F}");
+        assert_eq!(report.replacements_of(ReplacementKind::Bidi).count(), 4);
+
+        let options = SanitizeOptions {
+            strip_bidi: false,
+            ..SanitizeOptions::default()
+        };
+        let report =
+            MessageSanitizer::sanitize_with_options(message, &options, &EmojiTable::builtin());
+        assert_eq!(report.sanitized, message);
+    }
+
+    #[test]
+    fn test_emojis_only() {
+        let message = "\x1B[1mok\x1B[0m\r\n👩\u{200D}💻 \u{202E}x ✅";
+        let report = MessageSanitizer::sanitize_with_options(
+            message,
+            &SanitizeOptions::emojis_only(),
+            &EmojiTable::builtin(),
+        );
+
+        assert_eq!(
+            report.sanitized,
+            "\x1B[1mok\x1B[0m\r\n[]\u{200D}[] \u{202E}x ✅"
+        );
+        assert_eq!(report.replacements.len(), 2);
+    }
+
+    #[test]
+    fn test_replacements_in_message_order() {
+        let report = MessageSanitizer::sanitize_with_report("\x1B[32m🚀\x1B[0m\u{202E}ok\x07");
+        let kinds: Vec<_> = report.replacements.iter().map(|r| r.kind).collect();
+
+        assert_eq!(report.sanitized, "[]ok");
+        assert_eq!(
+            kinds,
+            vec![
+                ReplacementKind::Ansi,
+                ReplacementKind::Emoji,
+                ReplacementKind::Ansi,
+                ReplacementKind::Bidi,
+                ReplacementKind::Control,
+            ]
+        );
+        assert!(
+            report
+                .replacements
+                .windows(2)
+                .all(|pair| pair[0].byte_offset < pair[1].byte_offset)
+        );
+    }
+
+    #[test]
+    fn test_transliteration() {
+        let message = "Alerte « disque plein » — l’équipe s’en occupe… ça brûle ✅ 🚀";
+        let options = SanitizeOptions {
+            transliterate: true,
+            ..SanitizeOptions::default()
+        };
+        let report =
+            MessageSanitizer::sanitize_with_options(message, &options, &EmojiTable::builtin());
+
+        assert_eq!(
+            report.sanitized,
+            "Alerte \" disque plein \" - l'équipe s'en occupe... ca brule ✅ []"
+        );
+        let transliterated: Vec<_> = report
+            .replacements_of(ReplacementKind::Transliteration)
+            .collect();
+        assert_eq!(transliterated.len(), 8);
+        assert_eq!(transliterated[0].original, "«");
+        assert_eq!(transliterated[0].byte_offset, "Alerte ".len());
+
+        // Decomposed accents are composed when GSM-7 has them
+        let report = MessageSanitizer::sanitize_with_options(
+            "e\u{301}te\u{302}",
+            &options,
+            &EmojiTable::builtin(),
+        );
+        assert_eq!(report.sanitized, "éte");
+
+        // Opt-in only
+        assert_eq!(MessageSanitizer::sanitize("l’équipe…"), "l’équipe…");
+        assert_eq!(
+            MessageSanitizer::sanitize("1\u{FE0F}\u{20E3}"),
+            "1\u{FE0F}\u{20E3}"
+        );
+    }
+
+    #[test]
+    fn test_control_chars_names() {
+        for name in ControlChars::NAMES {
+            assert_eq!(name.parse::<ControlChars>().unwrap().to_string(), name);
+        }
+        assert!("strip".parse::<ControlChars>().is_err());
+    }
+
     #[test]
     fn test_no_emojis() {
         let input = "Simple text message";
@@ -0,0 +1,91 @@
+use serde::{Deserialize, Deserializer};
+use std::fmt;
+use zeroize::Zeroize;
+
+/// Placeholder printed instead of secret values
+pub const REDACTED: &str = "[REDACTED]";
+
+/// FreeMobile API key, kept out of logs and wiped from memory on drop
+///
+/// `Debug` and `Display` print a placeholder, and the key does not implement
+/// `Serialize`: use [`ApiKey::expose`] where the actual value is needed.
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::ApiKey;
+///
+/// let key = ApiKey::new("your-api-key".to_string());
+/// assert_eq!(format!("{:?}", key), "ApiKey([REDACTED])");
+/// assert_eq!(key.expose(), "your-api-key");
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct ApiKey(String);
+
+impl ApiKey {
+    pub fn new(key: String) -> Self {
+        Self(key)
+    }
+
+    /// Access the actual key
+    pub fn expose(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for ApiKey {
+    fn from(key: String) -> Self {
+        Self::new(key)
+    }
+}
+
+impl Drop for ApiKey {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl fmt::Debug for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ApiKey({})", REDACTED)
+    }
+}
+
+impl fmt::Display for ApiKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(REDACTED)
+    }
+}
+
+impl<'de> Deserialize<'de> for ApiKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redacted_formatting() {
+        let key = ApiKey::new("super-secret".to_string());
+
+        assert_eq!(format!("{:?}", key), "ApiKey([REDACTED])");
+        assert_eq!(key.to_string(), "[REDACTED]");
+        assert!(!format!("{:#?}", key).contains("super-secret"));
+    }
+
+    #[test]
+    fn test_expose() {
+        let key = ApiKey::from("super-secret".to_string());
+        assert_eq!(key.expose(), "super-secret");
+        assert_eq!(key.clone(), key);
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let key: ApiKey = serde_json::from_str("\"super-secret\"").unwrap();
+        assert_eq!(key.expose(), "super-secret");
+    }
+}
@@ -2,6 +2,12 @@
 // This list contains emojis that have been tested and confirmed to work
 // with the FreeMobile SMS API. All other emojis will be replaced during
 // message sanitization to ensure compatibility.
+//
+// The list can be extended at runtime with an `EmojiTable`, such as the one
+// generated by `send-sms probe-emojis`.
+
+use std::collections::HashSet;
+use std::sync::LazyLock;
 
 pub const SUPPORTED_EMOJIS: &[&str] = &[
     "#️⃣", "*️⃣", "0️⃣", "1️⃣", "2️⃣", "3️⃣", "4️⃣", "5️⃣", "6️⃣", "7️⃣", "8️⃣", "9️⃣", "©️", "®️", "‼️", "⁉️",
@@ -15,6 +21,123 @@ pub const SUPPORTED_EMOJIS: &[&str] = &[
     "➕", "➖", "➗", "➡️", "➰", "➿",
 ];
 
+// Closest supported emoji of common unsupported ones, used by
+// `SanitizePolicy::Closest`. Keys are written without variation selector.
+pub const CLOSEST_SUPPORTED_EMOJIS: &[(&str, &str)] = &[
+    // Alerts and statuses
+    ("🔥", "⚠️"),
+    ("🚨", "⚠️"),
+    ("💥", "⚠️"),
+    ("🐛", "⚠️"),
+    ("🆘", "‼️"),
+    ("🔔", "❕"),
+    ("🛑", "⛔"),
+    ("🚫", "⛔"),
+    ("🟢", "✅"),
+    ("🆗", "✅"),
+    ("👍", "✔️"),
+    ("👌", "✔️"),
+    ("💯", "✔️"),
+    ("🔴", "❌"),
+    ("👎", "✖️"),
+    ("🤔", "❔"),
+    ("💀", "☠️"),
+    ("💡", "ℹ️"),
+    ("🔁", "♻️"),
+    ("🔄", "♻️"),
+    ("👉", "➡️"),
+    // Faces and celebrations
+    ("😀", "☺️"),
+    ("😃", "☺️"),
+    ("😄", "☺️"),
+    ("😁", "☺️"),
+    ("😊", "☺️"),
+    ("🙂", "☺️"),
+    ("😉", "☺️"),
+    ("😢", "☹️"),
+    ("😞", "☹️"),
+    ("😟", "☹️"),
+    ("🙁", "☹️"),
+    ("😭", "☹️"),
+    ("🎉", "✨"),
+    ("🎊", "✨"),
+    ("🥳", "✨"),
+    ("🌟", "✨"),
+    ("💫", "✨"),
+    ("⭐", "✨"),
+    ("💙", "❤️"),
+    ("💚", "❤️"),
+    ("💛", "❤️"),
+    ("🧡", "❤️"),
+    ("💜", "❤️"),
+    ("💖", "❤️"),
+    // Objects
+    ("🚀", "✈️"),
+    ("🛫", "✈️"),
+    ("📱", "☎️"),
+    ("📞", "☎️"),
+    ("💻", "⚙️"),
+    ("🖥", "⚙️"),
+    ("🔧", "⚙️"),
+    ("🔨", "⚒️"),
+    ("🛠", "⚒️"),
+    ("📧", "✉️"),
+    ("📨", "✉️"),
+    ("📩", "✉️"),
+    ("💬", "✉️"),
+    ("📝", "✏️"),
+    ("🖊", "✒️"),
+    ("🍵", "☕️"),
+    ("🏀", "⚽"),
+    // Weather
+    ("🌞", "☀️"),
+    ("🌤", "⛅"),
+    ("🌧", "☔"),
+    ("🌦", "☔"),
+    ("🌨", "❄️"),
+    ("⛈", "⛈️"),
+];
+
+static SUPPORTED_EMOJI_SET: LazyLock<HashSet<&'static str>> =
+    LazyLock::new(|| SUPPORTED_EMOJIS.iter().copied().collect());
+
 pub fn is_supported_emoji(emoji: &str) -> bool {
-    SUPPORTED_EMOJIS.contains(&emoji)
+    SUPPORTED_EMOJI_SET.contains(emoji)
+}
+
+/// Closest supported emoji of an unsupported one, if any
+pub fn closest_supported_emoji(emoji: &str) -> Option<&'static str> {
+    let normalized = emoji.replace('\u{FE0F}', "");
+    CLOSEST_SUPPORTED_EMOJIS
+        .iter()
+        .find(|(unsupported, _)| *unsupported == normalized)
+        .map(|(_, supported)| *supported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_closest_emojis_are_supported() {
+        for (unsupported, supported) in CLOSEST_SUPPORTED_EMOJIS {
+            assert!(
+                !is_supported_emoji(unsupported),
+                "{} is supported",
+                unsupported
+            );
+            assert!(
+                is_supported_emoji(supported),
+                "{} is not supported",
+                supported
+            );
+        }
+    }
+
+    #[test]
+    fn test_closest_supported_emoji() {
+        assert_eq!(closest_supported_emoji("🔥"), Some("⚠️"));
+        assert_eq!(closest_supported_emoji("🖥️"), Some("⚙️"));
+        assert_eq!(closest_supported_emoji("🦀"), None);
+    }
 }
@@ -0,0 +1,132 @@
+//! Test helpers for code depending on `freemobile-api`
+//!
+//! Available with the `testing` cargo feature:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! freemobile-api = { version = "0.1.0", features = ["testing"] }
+//! ```
+
+use crate::error::FreeMobileError;
+use crate::transport::{SmsRequest, SmsTransport, TransportResponse};
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// Message captured by a [`RecordingTransport`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedMessage {
+    pub user: String,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+struct RecorderState {
+    messages: Vec<RecordedMessage>,
+    responses: VecDeque<TransportResponse>,
+}
+
+/// In-memory transport recording every message instead of sending it
+///
+/// Clones share the same recording, so a clone can be kept to inspect the
+/// messages after the original has been moved into a client.
+///
+/// Every request is answered with HTTP 200 unless status codes have been
+/// scripted with [`RecordingTransport::with_responses`] or
+/// [`RecordingTransport::push_response`]. Requests answered with an error
+/// status are recorded too.
+///
+/// # Example
+///
+/// ```
+/// use freemobile_api::{Credentials, FreeMobileClient};
+/// use freemobile_api::testing::RecordingTransport;
+///
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), freemobile_api::FreeMobileError> {
+/// let transport = RecordingTransport::new();
+/// let credentials = Credentials::new("12345678".to_string(), "key".to_string());
+/// let client = FreeMobileClient::builder(credentials).build_with_transport(transport.clone())?;
+///
+/// client.send("Hello 😀").await?;
+/// assert_eq!(transport.messages(), vec!["Hello []".to_string()]);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RecordingTransport {
+    state: Arc<Mutex<RecorderState>>,
+}
+
+impl RecordingTransport {
+    /// Create a transport answering every request with HTTP 200
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a transport answering requests with the given status codes, in order
+    ///
+    /// Once the scripted statuses are exhausted, requests are answered with HTTP 200.
+    pub fn with_responses(statuses: impl IntoIterator<Item = u16>) -> Self {
+        let transport = Self::new();
+        transport
+            .lock()
+            .responses
+            .extend(statuses.into_iter().map(TransportResponse::new));
+        transport
+    }
+
+    /// Queue a status code for a future request
+    pub fn push_response(&self, status: u16) {
+        self.lock()
+            .responses
+            .push_back(TransportResponse::new(status));
+    }
+
+    /// Queue a status code with a response body for a future request
+    pub fn push_response_with_body(&self, status: u16, body: impl Into<String>) {
+        self.lock().responses.push_back(TransportResponse {
+            status,
+            body: body.into(),
+        });
+    }
+
+    /// All recorded requests, in the order they were received
+    pub fn requests(&self) -> Vec<RecordedMessage> {
+        self.lock().messages.clone()
+    }
+
+    /// Text of all recorded messages, in the order they were received
+    pub fn messages(&self) -> Vec<String> {
+        self.lock()
+            .messages
+            .iter()
+            .map(|recorded| recorded.message.clone())
+            .collect()
+    }
+
+    /// Forget all recorded messages
+    pub fn clear(&self) {
+        self.lock().messages.clear();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, RecorderState> {
+        // A panic while holding the lock cannot leave the state inconsistent
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl SmsTransport for RecordingTransport {
+    async fn send(&self, request: SmsRequest<'_>) -> Result<TransportResponse, FreeMobileError> {
+        let mut state = self.lock();
+        state.messages.push(RecordedMessage {
+            user: request.credentials.user.clone(),
+            message: request.message.to_string(),
+        });
+        let response = state
+            .responses
+            .pop_front()
+            .unwrap_or_else(|| TransportResponse::new(200));
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,146 @@
+//! Transport layer used by [`FreeMobileClient`](crate::FreeMobileClient)
+//!
+//! The client delegates the actual delivery of each chunk to an [`SmsTransport`].
+//! [`ReqwestTransport`] is the default implementation and performs the HTTP GET
+//! request expected by the FreeMobile API. Alternative transports can be plugged
+//! in with [`FreeMobileClientBuilder::build_with_transport`](crate::FreeMobileClientBuilder::build_with_transport).
+
+use crate::client::Credentials;
+use crate::constants::MAX_ERROR_BODY_LENGTH;
+use crate::error::FreeMobileError;
+use reqwest::{Client, Url};
+use std::future::Future;
+
+/// A single message to deliver, already sanitized, chunked and formatted
+#[derive(Debug, Clone, Copy)]
+pub struct SmsRequest<'a> {
+    pub credentials: &'a Credentials,
+    pub message: &'a str,
+}
+
+/// Response returned by a transport for a delivered request
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TransportResponse {
+    /// HTTP status code returned by the API
+    pub status: u16,
+    /// Response body, only captured for error statuses and truncated to
+    /// `MAX_ERROR_BODY_LENGTH` bytes
+    pub body: String,
+}
+
+impl TransportResponse {
+    /// Create a response without body
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            body: String::new(),
+        }
+    }
+
+    /// Check if the status code reports a successful delivery
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Delivery mechanism for formatted message chunks
+///
+/// Implementations only deliver the request and report the status code;
+/// mapping error statuses to [`FreeMobileError`] is done by the client.
+pub trait SmsTransport: Send + Sync {
+    /// Deliver a single message
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request could not be delivered at all
+    /// (network failure, timeout...).
+    fn send(
+        &self,
+        request: SmsRequest<'_>,
+    ) -> impl Future<Output = Result<TransportResponse, FreeMobileError>> + Send;
+}
+
+/// Default transport sending messages with an HTTP GET request
+#[derive(Debug, Clone)]
+pub struct ReqwestTransport {
+    client: Client,
+    url: Url,
+}
+
+impl ReqwestTransport {
+    /// Create a transport sending requests to `url` with the given HTTP client
+    pub fn new(client: Client, url: Url) -> Self {
+        Self { client, url }
+    }
+
+    /// Endpoint messages are sent to
+    pub fn url(&self) -> &Url {
+        &self.url
+    }
+}
+
+impl SmsTransport for ReqwestTransport {
+    async fn send(&self, request: SmsRequest<'_>) -> Result<TransportResponse, FreeMobileError> {
+        let mut response = self
+            .client
+            .get(self.url.clone())
+            .query(&[
+                ("user", request.credentials.user.as_str()),
+                ("pass", request.credentials.pass.expose()),
+                ("msg", request.message),
+            ])
+            .send()
+            .await
+            .map_err(|e| FreeMobileError::HttpError(e.without_url()))?;
+
+        let status = response.status();
+        let mut body = Vec::new();
+        if !status.is_success() {
+            // Keep the beginning of the body to diagnose unexpected errors
+            while body.len() < MAX_ERROR_BODY_LENGTH {
+                match response.chunk().await {
+                    Ok(Some(chunk)) => body.extend_from_slice(&chunk),
+                    Ok(None) | Err(_) => break,
+                }
+            }
+        }
+
+        Ok(TransportResponse {
+            status: status.as_u16(),
+            body: truncate_body(&body),
+        })
+    }
+}
+
+/// Decode a response body, keeping at most `MAX_ERROR_BODY_LENGTH` bytes
+/// without cutting a character
+fn truncate_body(body: &[u8]) -> String {
+    let mut body = String::from_utf8_lossy(body).into_owned();
+    if body.len() > MAX_ERROR_BODY_LENGTH {
+        let end = (0..=MAX_ERROR_BODY_LENGTH)
+            .rev()
+            .find(|&index| body.is_char_boundary(index))
+            .unwrap_or(0);
+        body.truncate(end);
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_body_on_char_boundary() {
+        assert_eq!(truncate_body(b"Bad request"), "Bad request");
+
+        let body = format!("{}é and more", "a".repeat(MAX_ERROR_BODY_LENGTH - 1));
+        let truncated = truncate_body(body.as_bytes());
+        assert_eq!(truncated, "a".repeat(MAX_ERROR_BODY_LENGTH - 1));
+
+        let body = "€".repeat(MAX_ERROR_BODY_LENGTH);
+        let truncated = truncate_body(body.as_bytes());
+        assert!(truncated.len() <= MAX_ERROR_BODY_LENGTH);
+        assert!(truncated.chars().all(|c| c == '€'));
+    }
+}
@@ -0,0 +1,35 @@
+//! # FreeMobile Mock
+//!
+//! Local mock of the FreeMobile SMS API, for integration tests that must not
+//! reach the real service.
+//!
+//! ## Features
+//!
+//! - **Same contract as the real API**: `GET /sendmsg` with `user`, `pass` and `msg`
+//! - **Credential checks** against a configured list
+//! - **Scripted responses** returning 400/402/403/500 or delaying requests
+//! - **JSONL recording** of every received message
+//!
+//! ## Quick Start
+//!
+//! ```rust
+//! use freemobile_mock::MockServer;
+//!
+//! # #[tokio::main]
+//! # async fn main() -> std::io::Result<()> {
+//! let server = MockServer::builder()
+//!     .credentials("12345678", "secret")
+//!     .start()
+//!     .await?;
+//!
+//! // Point the client, or `send-sms --api-url`, to the mock
+//! println!("{}", server.url());
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod script;
+pub mod server;
+
+pub use script::ScriptedResponse;
+pub use server::{MockServer, MockServerBuilder, ReceivedMessage};
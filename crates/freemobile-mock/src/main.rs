@@ -0,0 +1,130 @@
+use clap::{Arg, ArgAction, Command};
+use freemobile_mock::{MockServer, ScriptedResponse};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::process;
+
+#[tokio::main]
+async fn main() {
+    if let Err(e) = run().await {
+        eprintln!("❌ Error: {}", e);
+        process::exit(1);
+    }
+}
+
+async fn run() -> Result<(), String> {
+    let matches = build_cli().get_matches();
+
+    let mut builder = MockServer::builder();
+    if let Some(addr) = matches.get_one::<SocketAddr>("listen") {
+        builder = builder.addr(*addr);
+    }
+    for credentials in matches
+        .get_many::<(String, String)>("credentials")
+        .unwrap_or_default()
+    {
+        builder = builder.credentials(credentials.0.clone(), credentials.1.clone());
+    }
+    if let Some(steps) = matches.get_many::<ScriptedResponse>("script") {
+        builder = builder.script(steps.copied());
+    }
+    if let Some(path) = matches.get_one::<PathBuf>("record") {
+        builder = builder.record_to(path);
+    }
+
+    let server = builder
+        .start()
+        .await
+        .map_err(|e| format!("Failed to start mock server: {}", e))?;
+    println!("🧪 FreeMobile mock listening on {}", server.url());
+
+    tokio::signal::ctrl_c()
+        .await
+        .map_err(|e| format!("Failed to listen for ctrl-c: {}", e))?;
+    println!("🛑 {} message(s) received", server.received().len());
+
+    Ok(())
+}
+
+fn build_cli() -> Command {
+    Command::new("freemobile-mock")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about("Local mock of the FreeMobile SMS API")
+        .arg(
+            Arg::new("listen")
+                .short('l')
+                .long("listen")
+                .value_name("ADDR")
+                .help("Address to listen on (defaults to a random port on 127.0.0.1)")
+                .value_parser(clap::value_parser!(SocketAddr)),
+        )
+        .arg(
+            Arg::new("credentials")
+                .short('c')
+                .long("credentials")
+                .value_name("USER:PASS")
+                .help("Accepted credentials, can be repeated (accepts any when omitted)")
+                .action(ArgAction::Append)
+                .value_parser(parse_credentials),
+        )
+        .arg(
+            Arg::new("script")
+                .short('s')
+                .long("script")
+                .value_name("STATUS[:DELAY_MS],...")
+                .help("Responses of the next requests, in order (200 only delays)")
+                .value_delimiter(',')
+                .value_parser(clap::value_parser!(ScriptedResponse)),
+        )
+        .arg(
+            Arg::new("record")
+                .short('r')
+                .long("record")
+                .value_name("PATH")
+                .help("Append received messages to a JSONL file")
+                .value_parser(clap::value_parser!(PathBuf)),
+        )
+}
+
+fn parse_credentials(value: &str) -> Result<(String, String), String> {
+    value
+        .split_once(':')
+        .filter(|(user, pass)| !user.is_empty() && !pass.is_empty())
+        .map(|(user, pass)| (user.to_string(), pass.to_string()))
+        .ok_or_else(|| "Credentials must be formatted as USER:PASS".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_credentials() {
+        assert_eq!(
+            parse_credentials("12345678:key:with:colons").unwrap(),
+            ("12345678".to_string(), "key:with:colons".to_string())
+        );
+        assert!(parse_credentials("12345678").is_err());
+        assert!(parse_credentials(":key").is_err());
+    }
+
+    #[test]
+    fn test_cli_script() {
+        let matches = build_cli()
+            .try_get_matches_from(["freemobile-mock", "-s", "402,500:100,200"])
+            .unwrap();
+        let steps: Vec<ScriptedResponse> = matches
+            .get_many::<ScriptedResponse>("script")
+            .unwrap()
+            .copied()
+            .collect();
+
+        assert_eq!(steps.len(), 3);
+        assert_eq!(steps[1].to_string(), "500:100");
+        assert!(
+            build_cli()
+                .try_get_matches_from(["freemobile-mock", "-s", "oops"])
+                .is_err()
+        );
+    }
+}
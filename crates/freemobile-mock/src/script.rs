@@ -0,0 +1,101 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Scripted behavior of the mock for a single request
+///
+/// A step delays the response, then answers with `status`. A step with status
+/// 200 only adds the delay: credentials are still checked and the message is
+/// accepted as usual.
+///
+/// Steps are parsed from `STATUS[:DELAY_MS]`, e.g. `402` or `200:1500`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScriptedResponse {
+    pub status: u16,
+    pub delay: Duration,
+}
+
+impl ScriptedResponse {
+    /// Answer with `status` immediately
+    pub fn status(status: u16) -> Self {
+        Self {
+            status,
+            delay: Duration::ZERO,
+        }
+    }
+
+    /// Accept the message as usual after `delay`
+    pub fn delayed(delay: Duration) -> Self {
+        Self { status: 200, delay }
+    }
+
+    /// Check if the step only delays a normal response
+    pub fn is_pass_through(&self) -> bool {
+        self.status == 200
+    }
+}
+
+impl FromStr for ScriptedResponse {
+    type Err = String;
+
+    fn from_str(step: &str) -> Result<Self, Self::Err> {
+        let (status, delay) = match step.trim().split_once(':') {
+            Some((status, delay)) => (status, Some(delay)),
+            None => (step.trim(), None),
+        };
+
+        let status = status
+            .parse::<u16>()
+            .ok()
+            .filter(|status| (100..600).contains(status))
+            .ok_or_else(|| format!("Invalid status in script step '{}'", step))?;
+        let delay = match delay {
+            Some(delay) => delay
+                .parse::<u64>()
+                .map(Duration::from_millis)
+                .map_err(|_| format!("Invalid delay in script step '{}'", step))?,
+            None => Duration::ZERO,
+        };
+
+        Ok(Self { status, delay })
+    }
+}
+
+impl fmt::Display for ScriptedResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.delay.is_zero() {
+            write!(f, "{}", self.status)
+        } else {
+            write!(f, "{}:{}", self.status, self.delay.as_millis())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_steps() {
+        assert_eq!(
+            "402".parse::<ScriptedResponse>().unwrap(),
+            ScriptedResponse::status(402)
+        );
+        assert_eq!(
+            "200:1500".parse::<ScriptedResponse>().unwrap(),
+            ScriptedResponse::delayed(Duration::from_millis(1500))
+        );
+        assert_eq!(
+            " 500:20 ".parse::<ScriptedResponse>().unwrap().to_string(),
+            "500:20"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_steps() {
+        assert!("".parse::<ScriptedResponse>().is_err());
+        assert!("abc".parse::<ScriptedResponse>().is_err());
+        assert!("999".parse::<ScriptedResponse>().is_err());
+        assert!("500:soon".parse::<ScriptedResponse>().is_err());
+    }
+}
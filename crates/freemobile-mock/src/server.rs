@@ -0,0 +1,457 @@
+use crate::script::ScriptedResponse;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, MutexGuard};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinHandle;
+
+/// Path served by the mock, as on the real API
+pub const SEND_PATH: &str = "/sendmsg";
+
+/// Upper bound of the request head read from a connection
+const MAX_REQUEST_SIZE: usize = 16 * 1024;
+
+/// Request received on `/sendmsg` with all its parameters
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReceivedMessage {
+    pub user: String,
+    pub msg: String,
+    /// Status the mock answered with
+    pub status: u16,
+}
+
+#[derive(Debug)]
+struct State {
+    credentials: Vec<(String, String)>,
+    script: VecDeque<ScriptedResponse>,
+    received: Vec<ReceivedMessage>,
+    record: Option<File>,
+}
+
+impl State {
+    /// Any credentials are accepted when none were configured
+    fn accepts(&self, user: &str, pass: &str) -> bool {
+        self.credentials.is_empty()
+            || self
+                .credentials
+                .iter()
+                .any(|(known_user, known_pass)| known_user == user && known_pass == pass)
+    }
+
+    fn answer(&mut self, params: Params, step: Option<ScriptedResponse>) -> u16 {
+        let status = match step {
+            Some(step) if !step.is_pass_through() => step.status,
+            _ if !self.accepts(&params.user, &params.pass) => 403,
+            _ => 200,
+        };
+
+        let received = ReceivedMessage {
+            user: params.user,
+            msg: params.msg,
+            status,
+        };
+        if let Some(ref mut file) = self.record
+            && let Err(e) = record(file, &received)
+        {
+            eprintln!("⚠️ Failed to record message: {}", e);
+        }
+        self.received.push(received);
+
+        status
+    }
+}
+
+/// Query parameters of a `/sendmsg` request
+#[derive(Debug)]
+struct Params {
+    user: String,
+    pass: String,
+    msg: String,
+}
+
+/// Builder for a [`MockServer`]
+///
+/// # Example
+///
+/// ```
+/// use freemobile_mock::{MockServer, ScriptedResponse};
+///
+/// # #[tokio::main]
+/// # async fn main() -> std::io::Result<()> {
+/// let server = MockServer::builder()
+///     .credentials("12345678", "secret")
+///     .script([ScriptedResponse::status(500)])
+///     .start()
+///     .await?;
+///
+/// println!("Send messages to {}", server.url());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockServerBuilder {
+    addr: SocketAddr,
+    credentials: Vec<(String, String)>,
+    script: Vec<ScriptedResponse>,
+    record_path: Option<PathBuf>,
+}
+
+impl MockServerBuilder {
+    fn new() -> Self {
+        Self {
+            addr: SocketAddr::from((Ipv4Addr::LOCALHOST, 0)),
+            credentials: Vec::new(),
+            script: Vec::new(),
+            record_path: None,
+        }
+    }
+
+    /// Set the address to listen on (defaults to a random port on localhost)
+    pub fn addr(mut self, addr: SocketAddr) -> Self {
+        self.addr = addr;
+        self
+    }
+
+    /// Accept the given credentials
+    ///
+    /// Without any configured credentials, every user and API key is accepted.
+    pub fn credentials(mut self, user: impl Into<String>, pass: impl Into<String>) -> Self {
+        self.credentials.push((user.into(), pass.into()));
+        self
+    }
+
+    /// Queue scripted responses, used by the next requests in order
+    pub fn script(mut self, steps: impl IntoIterator<Item = ScriptedResponse>) -> Self {
+        self.script.extend(steps);
+        self
+    }
+
+    /// Append every received message to a JSONL file
+    pub fn record_to(mut self, path: impl Into<PathBuf>) -> Self {
+        self.record_path = Some(path.into());
+        self
+    }
+
+    /// Bind the listener and start serving requests in the background
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the address cannot be bound or the record file
+    /// cannot be opened.
+    pub async fn start(self) -> io::Result<MockServer> {
+        let record = match self.record_path {
+            Some(path) => Some(OpenOptions::new().create(true).append(true).open(path)?),
+            None => None,
+        };
+        let listener = TcpListener::bind(self.addr).await?;
+        let addr = listener.local_addr()?;
+
+        let state = Arc::new(Mutex::new(State {
+            credentials: self.credentials,
+            script: self.script.into(),
+            received: Vec::new(),
+            record,
+        }));
+
+        let task = tokio::spawn(accept_loop(listener, Arc::clone(&state)));
+
+        Ok(MockServer { addr, state, task })
+    }
+}
+
+/// Local HTTP server mimicking the FreeMobile SMS API
+///
+/// Serves `GET /sendmsg?user=...&pass=...&msg=...` and answers like the real
+/// service: 400 when a parameter is missing, 403 for unknown credentials and
+/// 200 otherwise, unless a [`ScriptedResponse`] overrides the status.
+///
+/// The server stops when dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    task: JoinHandle<()>,
+}
+
+impl MockServer {
+    pub fn builder() -> MockServerBuilder {
+        MockServerBuilder::new()
+    }
+
+    /// Address the server is listening on
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Endpoint to use as API URL
+    pub fn url(&self) -> String {
+        format!("http://{}{}", self.addr, SEND_PATH)
+    }
+
+    /// Queue a scripted response after the pending ones
+    pub fn push_script(&self, step: ScriptedResponse) {
+        lock(&self.state).script.push_back(step);
+    }
+
+    /// All requests received on `/sendmsg`, in order, whatever their status
+    pub fn received(&self) -> Vec<ReceivedMessage> {
+        lock(&self.state).received.clone()
+    }
+
+    /// Text of the messages accepted with HTTP 200, in order
+    pub fn messages(&self) -> Vec<String> {
+        lock(&self.state)
+            .received
+            .iter()
+            .filter(|received| received.status == 200)
+            .map(|received| received.msg.clone())
+            .collect()
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    // A panic while holding the lock cannot leave the state inconsistent
+    state.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn record(file: &mut File, received: &ReceivedMessage) -> io::Result<()> {
+    let line = serde_json::to_string(received)?;
+    writeln!(file, "{}", line)?;
+    file.flush()
+}
+
+async fn accept_loop(listener: TcpListener, state: Arc<Mutex<State>>) {
+    loop {
+        match listener.accept().await {
+            Ok((stream, _)) => {
+                tokio::spawn(handle_connection(stream, Arc::clone(&state)));
+            }
+            Err(e) => eprintln!("⚠️ Failed to accept connection: {}", e),
+        }
+    }
+}
+
+async fn handle_connection(mut stream: TcpStream, state: Arc<Mutex<State>>) {
+    let Some(head) = read_request_head(&mut stream).await else {
+        return;
+    };
+
+    let status = match parse_request(&head) {
+        Ok(params) => {
+            let step = lock(&state).script.pop_front();
+            if let Some(step) = step {
+                tokio::time::sleep(step.delay).await;
+            }
+            lock(&state).answer(params, step)
+        }
+        Err(status) => status,
+    };
+
+    let reason = reason_phrase(status);
+    let body = if status == 200 { "" } else { reason };
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        reason,
+        body.len(),
+        body
+    );
+
+    // The client may have given up waiting, there is nobody left to report to
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Read the request line and headers, up to the blank line
+async fn read_request_head(stream: &mut TcpStream) -> Option<String> {
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 1024];
+
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        if head.len() > MAX_REQUEST_SIZE {
+            return None;
+        }
+        let read = stream.read(&mut buffer).await.ok()?;
+        if read == 0 {
+            return None;
+        }
+        head.extend_from_slice(&buffer[..read]);
+    }
+
+    Some(String::from_utf8_lossy(&head).into_owned())
+}
+
+/// Extract the parameters of a `/sendmsg` request, or the status to answer with
+fn parse_request(head: &str) -> Result<Params, u16> {
+    let mut request_line = head.lines().next().unwrap_or_default().split(' ');
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return Err(400);
+    };
+
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    if path != SEND_PATH {
+        return Err(404);
+    }
+    if method != "GET" {
+        return Err(405);
+    }
+
+    let mut params: HashMap<String, String> = form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect();
+    match (
+        params.remove("user"),
+        params.remove("pass"),
+        params.remove("msg"),
+    ) {
+        (Some(user), Some(pass), Some(msg)) => Ok(Params { user, pass, msg }),
+        _ => Err(400),
+    }
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        402 => "Payment Required",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Scripted Response",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use freemobile_api::{Credentials, FreeMobileClient, FreeMobileError, RetryPolicy};
+    use std::time::Duration;
+    use tempfile::NamedTempFile;
+
+    fn client(server: &MockServer, pass: &str) -> FreeMobileClient {
+        FreeMobileClient::builder(Credentials::new("12345678".to_string(), pass.to_string()))
+            .base_url(server.url())
+            .chunk_delay(Duration::ZERO)
+            .retry_policy(RetryPolicy::none())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_parse_request() {
+        let params = parse_request(
+            "GET /sendmsg?user=12345678&pass=key&msg=Hello%20world%20%F0%9F%98%80 HTTP/1.1\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(params.user, "12345678");
+        assert_eq!(params.pass, "key");
+        assert_eq!(params.msg, "Hello world 😀");
+
+        assert_eq!(
+            parse_request("GET /sendmsg?user=1&pass=2 HTTP/1.1").unwrap_err(),
+            400
+        );
+        assert_eq!(parse_request("GET /other HTTP/1.1").unwrap_err(), 404);
+        assert_eq!(parse_request("POST /sendmsg HTTP/1.1").unwrap_err(), 405);
+        assert_eq!(parse_request("").unwrap_err(), 400);
+    }
+
+    #[tokio::test]
+    async fn test_accepts_valid_credentials() {
+        let server = MockServer::builder()
+            .credentials("12345678", "secret")
+            .start()
+            .await
+            .unwrap();
+
+        client(&server, "secret").send("Hello 😀").await.unwrap();
+        let result = client(&server, "wrong").send("Rejected").await;
+
+        assert!(matches!(result, Err(FreeMobileError::AccessDenied)));
+        assert_eq!(server.messages(), vec!["Hello []"]);
+        assert_eq!(server.received()[1].status, 403);
+    }
+
+    #[tokio::test]
+    async fn test_scripted_statuses() {
+        let server = MockServer::builder()
+            .script([ScriptedResponse::status(500), ScriptedResponse::status(402)])
+            .start()
+            .await
+            .unwrap();
+        let client = client(&server, "any");
+
+        assert!(matches!(
+            client.send("first").await,
+            Err(FreeMobileError::ServerError)
+        ));
+        assert!(matches!(
+            client.send("second").await,
+            Err(FreeMobileError::TooManyRequests)
+        ));
+        client.send("third").await.unwrap();
+
+        server.push_script(ScriptedResponse::status(400));
+        assert!(matches!(
+            client.send("fourth").await,
+            Err(FreeMobileError::InvalidCredentials)
+        ));
+        assert_eq!(server.messages(), vec!["third"]);
+    }
+
+    #[tokio::test]
+    async fn test_scripted_delay() {
+        let server = MockServer::builder()
+            .script([ScriptedResponse::delayed(Duration::from_millis(500))])
+            .start()
+            .await
+            .unwrap();
+        let client =
+            FreeMobileClient::builder(Credentials::new("12345678".to_string(), "key".to_string()))
+                .base_url(server.url())
+                .timeout(Duration::from_millis(100))
+                .retry_policy(RetryPolicy::none())
+                .build()
+                .unwrap();
+
+        let error = client.send("too slow").await.unwrap_err();
+        assert!(matches!(error, FreeMobileError::HttpError(ref e) if e.is_timeout()));
+        client.send("on time").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_records_jsonl() {
+        let file = NamedTempFile::new().unwrap();
+        let server = MockServer::builder()
+            .credentials("12345678", "secret")
+            .record_to(file.path())
+            .start()
+            .await
+            .unwrap();
+
+        client(&server, "secret").send("recorded").await.unwrap();
+        let _ = client(&server, "wrong").send("rejected").await;
+
+        let lines: Vec<ReceivedMessage> = std::fs::read_to_string(file.path())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines, server.received());
+        assert_eq!(lines[0].msg, "recorded");
+        assert_eq!(lines[1].status, 403);
+    }
+}